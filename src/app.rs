@@ -1,17 +1,28 @@
-use crate::config::Config;
+use crate::config::{is_language_tag, is_region_code, Config};
 use crate::jobs::Job;
-use crate::movies::{Movie, UserMovie};
-use crate::production::{ListOrdering, EntryType, ListEntry, Production, ProdEntry, ListFiltering};
-use crate::series::{SearchedSeries, UserSeries, Series};
-use crate::themoviedb::{TheMovieDB, Width};
-use crate::view::{LicenseView, MovieView, SeriesView, TrailersView};
+use crate::movies::{Movie, MovieFullDetails, UserMovie};
+use crate::production::{
+    ListOrdering, EntryType, ListEntry, Production, ProdEntry, ListFiltering, ProductionPage, Genre, LibraryIds,
+    PagedSources, WatchProvider, WatchProviders, Keyword, ProductionIds, Trailer,
+};
+use crate::releases::{find_country, CountryRelease};
+use crate::search::{parse_external_link, DiscoverParams, DiscoverSort, ExternalLink, SearchParams};
+use crate::series::{SearchedSeries, SeriesDetails, SeriesFullDetails, UserSeries, Series};
+use crate::images::{ImageConfiguration, ImageKind};
+use crate::refresh::{movie_changes, series_changes, RefreshOutcome, Refreshed};
+use crate::themoviedb::{TheMovieDB, TmdbError, TmdbResult};
+use crate::person::SearchedPerson;
+use crate::view::{draw_watch_providers, ArtworkView, CollectionView, EpisodeView, LicenseView, MovieView, PersonView, RefreshReportView, SeriesView, TasksView, TrailersView,
+    ViewAction,
+};
 
 use std::collections::{HashMap, hash_map};
 use std::ops::RangeInclusive;
 use std::rc::Rc;
+use std::time::{Duration, Instant};
 
 use crate::production;
-use egui::{include_image, Align, Layout, Pos2, Rect, TopBottomPanel, Ui, Vec2, Visuals};
+use egui::{include_image, Align, Layout, Pos2, Rect, TopBottomPanel, Ui, Vec2, Visuals, WidgetText};
 use egui_toast::{Toast, ToastKind, ToastOptions, Toasts};
 
 pub struct MovieApp {
@@ -19,8 +30,12 @@ pub struct MovieApp {
     search: String,
     show_adult_content: bool,
 
-    search_productions: Option<Rc<[Production]>>,
-    fetch_productions_job: Job<Vec<Production>>,
+    search_mode: SearchMode,
+    search_options: SearchOptions,
+    search_results: PagedProductions,
+    left_tab: LeftTab,
+    // One per Feed::ALL entry, loaded the first time its tab is opened
+    feeds: Vec<PagedProductions>,
 
     description_cache: HashMap<u32, String>,
 
@@ -35,7 +50,12 @@ pub struct MovieApp {
     central_draw_list: Vec<ListEntry>,
     central_ordering:  ListOrdering,
     central_filtering: ListFiltering,
+    // Entries are sorted by their first genre and drawn under its heading
+    group_by_genre:    bool,
     searched_string:   String,
+    // Saved with the library
+    original_titles:   bool,
+    refreshed_at:      Option<u64>,
 
     // Right panel
     user_movies:    Vec<UserMovie>,
@@ -44,6 +64,21 @@ pub struct MovieApp {
     selection:      Selection,
 
     toasts: Toasts,
+    poster_downloads: Vec<Job<TmdbResult<u64>>>,
+    // Keyed by (is_tv, id), shared by the right panel and the "my services" filter
+    watch_providers: HashMap<(bool, u32), Job<TmdbResult<WatchProviders>>>,
+    // Providers that can be subscribed to in the configured region
+    region_providers: Job<TmdbResult<Vec<WatchProvider>>>,
+    // Age ratings being looked up for library entries
+    certification_jobs: Vec<(EntryType, Job<TmdbResult<Vec<CountryRelease>>>)>,
+    // Context menu actions waiting on TMDB, the entry shows a spinner meanwhile.
+    // Repeated actions are answered from the response cache, there's no separate copy of the details.
+    pending_actions: Vec<PendingAction>,
+    // A pasted or dropped link being resolved, the text is kept for messages
+    link_lookup: Job<TmdbResult<Option<Production>>>,
+    looked_up_link: String,
+    // One entry or the whole library, only one refresh runs at a time
+    refresh: Job<RefreshOutcome>,
 
     // Top panel
     merge_path: String,
@@ -51,8 +86,20 @@ pub struct MovieApp {
     // View states
     series_view: SeriesView,
     movie_view: MovieView,
+    person_view: PersonView,
+    collection_view: CollectionView,
+    episode_view: EpisodeView,
+    artwork_view: ArtworkView,
     trailers_view: TrailersView,
     license_view: LicenseView,
+    refresh_report: RefreshReportView,
+    tasks_view: TasksView,
+    genres: GenreCatalog,
+    image_configuration: Job<TmdbResult<ImageConfiguration>>,
+    discover_options: DiscoverOptions,
+    discover_results: PagedProductions,
+    related_title: String,
+    related_results: PagedProductions,
 
     // Not a part of the layout
     movie_db: TheMovieDB,
@@ -69,15 +116,17 @@ impl MovieApp {
 
         // NOTE: TheMovieDB SHOULDN'T hold the api_key, this struct is dumb!
         //       #AbolishTheMovieDB
-        let key = config.access_token.clone();
-        let movie_db = TheMovieDB::new(key, config.enable_cache);
+        let movie_db = TheMovieDB::new(&config);
 
         Self {
             search: String::new(),
             show_adult_content: config.include_adult,
-            search_productions: None,
+            search_mode: SearchMode::All,
+            search_options: SearchOptions::new(),
+            search_results: PagedProductions::new(),
+            left_tab: LeftTab::Search,
+            feeds: Feed::ALL.iter().map(|_| PagedProductions::new()).collect(),
             description_cache: HashMap::new(),
-            fetch_productions_job: Job::Empty,
 
             user_movies: Vec::new(),
             user_series: Vec::new(),
@@ -90,18 +139,41 @@ impl MovieApp {
             central_draw_list: Vec::new(),
             central_ordering: ListOrdering::UserDefined,
             central_filtering: ListFiltering::new(),
+            group_by_genre: false,
             searched_string: String::new(),
+            original_titles: false,
+            refreshed_at: None,
 
             toasts: Toasts::new()
                 .anchor(egui::Align2::RIGHT_TOP, (1.0, 1.0))
                 .direction(egui::Direction::TopDown),
+            poster_downloads: Vec::new(),
+            watch_providers: HashMap::new(),
+            region_providers: Job::Empty,
+            certification_jobs: Vec::new(),
+            pending_actions: Vec::new(),
+            link_lookup: Job::Empty,
+            looked_up_link: String::new(),
+            refresh: Job::Empty,
 
             merge_path: "".into(),
 
             series_view: SeriesView::new(),
             movie_view: MovieView::new(),
+            person_view: PersonView::new(),
+            collection_view: CollectionView::new(),
+            episode_view: EpisodeView::new(),
+            artwork_view: ArtworkView::new(),
             trailers_view: TrailersView::new(),
             license_view: LicenseView::new(),
+            refresh_report: RefreshReportView::new(),
+            tasks_view: TasksView::new(),
+            genres: GenreCatalog::new(),
+            image_configuration: Job::Empty,
+            discover_options: DiscoverOptions::new(),
+            discover_results: PagedProductions::new(),
+            related_title: String::new(),
+            related_results: PagedProductions::new(),
 
             movie_db,
             config,
@@ -136,19 +208,21 @@ impl MovieApp {
     }
 
     fn central_list_add_movie(&mut self, movie: &UserMovie) {
-        let entry = ListEntry::from_movie(movie);
+        let mut entry = ListEntry::from_movie(movie, self.original_titles);
+        entry.genres = self.genres.names(false, &movie.movie.genre_ids);
         self.central_user_list.push(entry);
         self.central_draw_list_update();
     }
 
     fn central_list_add_series(&mut self, series: &UserSeries) {
-        let entry = ListEntry::from_series(series);
+        let mut entry = ListEntry::from_series(series, self.original_titles);
+        entry.genres = self.genres.names(true, &series.series.genre_ids);
         self.central_user_list.push(entry);
         self.central_draw_list_update();
     }
 
     fn central_user_list_move_down(&mut self, index: usize) {
-        if !matches!(self.central_ordering, ListOrdering::UserDefined) {
+        if !matches!(self.central_ordering, ListOrdering::UserDefined) || self.group_by_genre {
             return;
         } 
 
@@ -166,7 +240,7 @@ impl MovieApp {
     }
 
     fn central_user_list_move_up(&mut self, index: usize) {
-        if !matches!(self.central_ordering, ListOrdering::UserDefined) {
+        if !matches!(self.central_ordering, ListOrdering::UserDefined) || self.group_by_genre {
             return;
         } 
 
@@ -185,6 +259,9 @@ impl MovieApp {
 
     fn central_draw_list_update(&mut self) {
         self.central_draw_list.clear();
+        if self.central_filtering.filter_my_services {
+            self.fetch_library_providers();
+        }
 
         let searched_lower = self.searched_string.to_lowercase();
 
@@ -202,6 +279,33 @@ impl MovieApp {
                 continue;
             }
 
+            if let Some(certification) = &self.central_filtering.certification {
+                if entry.certification.as_ref() != Some(certification) {
+                    continue;
+                }
+            }
+
+            if let Some(genre) = &self.central_filtering.genre {
+                if !entry.genres.contains(genre) {
+                    continue;
+                }
+            }
+
+            if self.central_filtering.filter_my_services {
+                let key = match entry.production_id {
+                    EntryType::Movie(id) => (false, id),
+                    EntryType::Series(id) => (true, id),
+                    EntryType::None => continue,
+                };
+                // Entries still being looked up are hidden until their providers arrive
+                let Some(Job::Finished(Ok(providers))) = self.watch_providers.get(&key) else {
+                    continue;
+                };
+                if !providers.is_on_any(&self.config.subscribed_providers) {
+                    continue;
+                }
+            }
+
             if !entry.name.to_lowercase().contains(&searched_lower) {
                 continue;
             }
@@ -223,6 +327,14 @@ impl MovieApp {
                 self.central_draw_list .sort_by(|a, b| b.rating.partial_cmp(&a.rating).unwrap()),
         }
 
+        if self.group_by_genre {
+            // Stable, so the ordering above still applies within a genre. Entries without genres go last.
+            self.central_draw_list.sort_by(|a, b| match (a.genres.first(), b.genres.first()) {
+                (Some(a), Some(b)) => a.cmp(b),
+                (a, b) => a.is_none().cmp(&b.is_none()),
+            });
+        }
+
         // // NOTE: Definitely needs some improvements, but will do for now.
         // //       Also, fuzzy searching would be really nice!
         // let matches = self.central_draw_list.iter().filter(|entry| {
@@ -348,7 +460,13 @@ impl MovieApp {
     }
 
     pub fn save_data(&mut self) {
-        let outcome = production::serialize_user_productions(&self.user_series, &self.user_movies, &self.prod_positions);
+        let outcome = production::serialize_user_productions(
+            &self.user_series,
+            &self.user_movies,
+            &self.prod_positions,
+            self.original_titles,
+            self.refreshed_at,
+        );
         match outcome {
             Ok(_) => {
                 self.toasts.add(Toast {
@@ -374,6 +492,10 @@ impl MovieApp {
         }
     }
 
+    pub fn flush_cache(&self) {
+        self.movie_db.cache().flush();
+    }
+
     pub fn load_data(&mut self) {
         let outcome = production::deserialize_user_productions(None);
         match outcome {
@@ -381,6 +503,8 @@ impl MovieApp {
                 self.user_series = user_data.user_series;
                 self.user_movies = user_data.user_movies;
                 self.prod_positions = user_data.prod_positions;
+                self.original_titles = user_data.original_titles;
+                self.refreshed_at = user_data.refreshed_at;
                 self.toasts.add(Toast {
                     text: "Loaded productions".into(),
                     kind: ToastKind::Success,
@@ -409,7 +533,7 @@ impl MovieApp {
     fn fix_data_integrity(&mut self) {
         let changes = production::fix_data_integrity(&mut self.user_series, &mut self.user_movies, &mut self.prod_positions);
         let text = if changes == 0 {
-            format!("No changes were made")
+            "No changes were made".to_string()
         } else {
             format!("Made {changes} different changes to user data")
         };
@@ -432,6 +556,7 @@ impl MovieApp {
             let new_data = UserMovie::new(movie.clone());
             self.central_list_add_movie(&new_data);
             self.user_movies.push(new_data);
+            self.fetch_certification(EntryType::Movie(movie.id));
         }
     }
 
@@ -442,9 +567,221 @@ impl MovieApp {
 
         if !exists {
             self.prod_positions.push(ProdEntry::new(false, series.id));
+            let id = series.id;
             let new_data = UserSeries::new(series);
             self.central_list_add_series(&new_data);
             self.user_series.push(new_data);
+            self.fetch_certification(EntryType::Series(id));
+        }
+    }
+
+    // Search results don't carry seasons, so the details have to be fetched first
+    fn add_searched_series(&mut self, series: &SearchedSeries) {
+        if self.is_action_pending(true, series.id, EntryAction::AddSeries) {
+            return;
+        }
+        let job = self.movie_db.get_series_details(series.id);
+        self.start_entry_action(true, series.id, &series.name, EntryAction::AddSeries, PendingFetch::SeriesToAdd(series.clone(), job));
+    }
+
+    fn is_entry_pending(&self, is_tv: bool, id: u32) -> bool {
+        self.pending_actions.iter().any(|pending| pending.is_tv == is_tv && pending.id == id)
+    }
+
+    // Clicking again while the same action is pending does nothing, checked before anything is fetched
+    fn is_action_pending(&self, is_tv: bool, id: u32, action: EntryAction) -> bool {
+        self.pending_actions
+            .iter()
+            .any(|pending| pending.is_tv == is_tv && pending.id == id && pending.actions.contains(&action))
+    }
+
+    fn start_entry_action(&mut self, is_tv: bool, id: u32, title: &str, action: EntryAction, fetch: PendingFetch) {
+        self.pending_actions.push(PendingAction {
+            is_tv,
+            id,
+            title: title.to_string(),
+            actions: vec![action],
+            fetch,
+        });
+    }
+
+    // Another action on an entry whose full details are already on the way waits for the same response
+    fn join_details_fetch(&mut self, is_tv: bool, id: u32, action: EntryAction) -> bool {
+        let pending = self.pending_actions.iter_mut().find(|pending| {
+            pending.is_tv == is_tv && pending.id == id && !matches!(pending.fetch, PendingFetch::SeriesToAdd(..))
+        });
+        match pending {
+            Some(pending) => {
+                if !pending.actions.contains(&action) {
+                    pending.actions.push(action);
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn movie_details_action(&mut self, movie: &Movie, action: EntryAction) {
+        if self.join_details_fetch(false, movie.id, action) {
+            return;
+        }
+        let job = self.movie_db.get_movie_full_details(movie.id);
+        self.start_entry_action(false, movie.id, &movie.title, action, PendingFetch::Movie(job));
+    }
+
+    fn series_details_action(&mut self, series: &SearchedSeries, action: EntryAction) {
+        if self.join_details_fetch(true, series.id, action) {
+            return;
+        }
+        let job = self.movie_db.get_series_full_details(series.id);
+        self.start_entry_action(true, series.id, &series.name, action, PendingFetch::Series(job));
+    }
+
+    fn poll_pending_actions(&mut self) {
+        let mut i = 0;
+        while i < self.pending_actions.len() {
+            if !self.pending_actions[i].fetch.poll() {
+                i += 1;
+                continue;
+            }
+            let pending = self.pending_actions.remove(i);
+            self.apply_entry_action(pending);
+        }
+    }
+
+    fn apply_entry_action(&mut self, pending: PendingAction) {
+        let PendingAction { title, actions, fetch, .. } = pending;
+        let labels: Vec<&str> = actions.iter().map(EntryAction::label).collect();
+        let labels = labels.join(" and ");
+        let (external_ids, trailers, keywords) = match fetch {
+            PendingFetch::SeriesToAdd(series, mut job) => {
+                match job.poll_owned() {
+                    Some(Ok(details)) => {
+                        self.add_series(Series::from(&series, details));
+                        self.toasts.add(Toast {
+                            text: format!("Added {title} to your library").into(),
+                            kind: ToastKind::Success,
+                            options: ToastOptions::default()
+                                .duration_in_seconds(2.5)
+                                .show_progress(true)
+                                .show_icon(true),
+                        });
+                    }
+                    Some(Err(err)) => {
+                        self.toasts.add(error_toast(format!("Failed to add {title}: {err}")));
+                    }
+                    None => {}
+                }
+                return;
+            }
+            PendingFetch::Movie(mut job) => match job.poll_owned() {
+                Some(Ok(full_details)) => (full_details.external_ids, full_details.trailers, full_details.keywords),
+                Some(Err(err)) => {
+                    self.toasts.add(error_toast(format!("Failed to {labels} for {title}: {err}")));
+                    return;
+                }
+                None => return,
+            },
+            PendingFetch::Series(mut job) => match job.poll_owned() {
+                Some(Ok(full_details)) => (full_details.external_ids, full_details.trailers, full_details.keywords),
+                Some(Err(err)) => {
+                    self.toasts.add(error_toast(format!("Failed to {labels} for {title}: {err}")));
+                    return;
+                }
+                None => return,
+            },
+        };
+
+        for action in actions {
+            self.run_entry_action(&title, action, &external_ids, &trailers, &keywords);
+        }
+    }
+
+    fn run_entry_action(
+        &mut self,
+        title: &str,
+        action: EntryAction,
+        external_ids: &ProductionIds,
+        trailers: &[Trailer],
+        keywords: &[Keyword],
+    ) {
+        match action {
+            EntryAction::OpenImdb => {
+                let url = external_ids.imdb_url(title);
+                let browser = &self.config.browser_name;
+                let _ = open::with_in_background(url, browser);
+            }
+            EntryAction::ShowKeywords => {
+                let text = if keywords.is_empty() {
+                    format!("{title} has no keywords")
+                } else {
+                    let names: Vec<&str> = keywords.iter().map(|keyword| keyword.name.as_str()).collect();
+                    format!("Keywords for {title}: {}", names.join(", "))
+                };
+                self.toasts.add(Toast {
+                    text: text.into(),
+                    kind: ToastKind::Info,
+                    options: ToastOptions::default()
+                        .duration_in_seconds(8.0)
+                        .show_progress(true)
+                        .show_icon(true),
+                });
+            }
+            EntryAction::ShowTrailers => self.trailers_view.set_content(title.to_string(), trailers.to_vec()),
+            EntryAction::AddSeries => unreachable!(),
+        }
+    }
+
+    fn handle_view_action(&mut self, action: ViewAction) {
+        match action {
+            ViewAction::OpenPerson(id, name) => self.person_view.set_person(id, name, &self.movie_db),
+            ViewAction::AddProduction(Production::Movie(movie)) => self.add_movie(movie),
+            ViewAction::AddProduction(Production::SearchedSeries(series)) => self.add_searched_series(&series),
+            ViewAction::AddProduction(Production::Person(_)) => {}
+            ViewAction::OpenArtwork(is_tv, id, title, images) => self.artwork_view.set_images(is_tv, id, title, images),
+            ViewAction::SetPoster(false, id, poster) => {
+                if let Some(user_movie) = self.user_movies.iter_mut().find(|user_movie| user_movie.movie.id == id) {
+                    user_movie.custom_poster = poster;
+                    self.central_list_reload();
+                }
+            }
+            ViewAction::SetPoster(true, id, poster) => {
+                if let Some(user_series) = self.user_series.iter_mut().find(|user_series| user_series.series.id == id) {
+                    user_series.custom_poster = poster;
+                    self.central_list_reload();
+                }
+            }
+            ViewAction::SetBackdrop(id, backdrop) => {
+                if let Some(user_movie) = self.user_movies.iter_mut().find(|user_movie| user_movie.movie.id == id) {
+                    user_movie.custom_backdrop = backdrop.clone();
+                    self.movie_view.set_header(id, backdrop);
+                }
+            }
+            ViewAction::ShowTrailers(title, trailers) => self.trailers_view.set_content(title, trailers),
+            ViewAction::OpenCollection(id, name) => self.collection_view.set_collection(id, name, &self.movie_db),
+            ViewAction::OpenEpisode(series_id, series_name, season_number, episode_number) => {
+                let note = self.episode_note(series_id, season_number, episode_number);
+                self.episode_view.set_episode(series_id, &series_name, season_number, episode_number, note, &self.movie_db);
+            }
+            ViewAction::SetEpisodeNote(series_id, season_number, episode_number, note) => {
+                if let Some(user_series) = self.user_series.iter_mut().find(|user_series| user_series.series.id == series_id) {
+                    user_series.set_episode_note(season_number, episode_number, note);
+                }
+            }
+            ViewAction::AddMovies(movies) => {
+                let count = movies.len();
+                for movie in movies {
+                    self.add_movie(movie);
+                }
+                self.toasts.add(Toast {
+                    text: format!("Added {count} movies to your library").into(),
+                    kind: ToastKind::Success,
+                    options: ToastOptions::default()
+                        .duration_in_seconds(2.5)
+                        .show_progress(true)
+                        .show_icon(true),
+                });
+            }
         }
     }
 
@@ -481,14 +818,399 @@ impl MovieApp {
             self.load_data();
         }
 
+        self.genres.load(&self.movie_db);
+        self.image_configuration = self.movie_db.get_image_configuration();
+        self.central_list_reload();
+    }
+
+    fn poll_image_configuration(&mut self) {
+        match self.image_configuration.poll_owned() {
+            Some(Ok(configuration)) => self.movie_db.image_configuration = configuration,
+            // The documented sizes are good enough until the next start
+            Some(Err(err)) => eprintln!("Failed to fetch the image configuration: {err}"),
+            None => {}
+        }
+    }
+
+    // Some even when nothing was written yet, as long as the note can be kept
+    fn episode_note(&self, series_id: u32, season_number: u32, episode_number: u32) -> Option<String> {
+        let user_series = self.user_series.iter().find(|user_series| user_series.series.id == series_id)?;
+        if season_number == 0 || season_number as usize > user_series.season_notes.len() {
+            return None;
+        }
+        Some(user_series.episode_note(season_number, episode_number).cloned().unwrap_or_default())
+    }
+
+    fn look_up_link(&mut self, link: ExternalLink, text: String) {
+        if self.link_lookup.is_running() {
+            return;
+        }
+        self.link_lookup = self.movie_db.find_by_link(link);
+        self.looked_up_link = text;
+    }
+
+    // A link dragged from the browser arrives as text, a shortcut file as its contents
+    fn handle_dropped_files(&mut self, ctx: &egui::Context) {
+        let dropped = ctx.input(|i| i.raw.dropped_files.clone());
+        for file in dropped {
+            let text = match (&file.bytes, &file.path) {
+                (Some(bytes), _) => String::from_utf8_lossy(bytes).into_owned(),
+                (None, Some(path)) => std::fs::read_to_string(path).unwrap_or_else(|_| path.display().to_string()),
+                (None, None) => continue,
+            };
+            match parse_external_link(&text) {
+                Some(link) => {
+                    self.look_up_link(link, file.name.clone());
+                    return;
+                }
+                None => {
+                    self.toasts.add(error_toast(format!("No TMDB, IMDb or Letterboxd link in {}", file.name)));
+                }
+            }
+        }
+    }
+
+    fn poll_link_lookup(&mut self) {
+        let Some(result) = self.link_lookup.poll_owned() else {
+            return;
+        };
+        let production = match result {
+            Ok(Some(production)) => production,
+            Ok(None) => {
+                self.toasts.add(error_toast(format!("Nothing on TMDB matches {}", self.looked_up_link)));
+                return;
+            }
+            Err(err) => {
+                self.toasts.add(error_toast(format!("Failed to look up {}: {err}", self.looked_up_link)));
+                return;
+            }
+        };
+        let library = LibraryIds::new(&self.user_movies, &self.user_series);
+        let (title, exists) = match &production {
+            Production::Movie(movie) => (movie.title.clone(), library.contains_entry(false, movie.id)),
+            Production::SearchedSeries(series) => (series.name.clone(), library.contains_entry(true, series.id)),
+            Production::Person(person) => {
+                self.toasts.add(error_toast(format!("{} is a person, not a title", person.name)));
+                return;
+            }
+        };
+        if exists {
+            self.toasts.add(Toast {
+                text: format!("{title} is already in your library").into(),
+                kind: ToastKind::Info,
+                options: ToastOptions::default()
+                    .duration_in_seconds(2.5)
+                    .show_progress(true)
+                    .show_icon(true),
+            });
+            return;
+        }
+        self.search.clear();
+        // A series is added once its details arrive, that reports itself
+        match production {
+            Production::Movie(movie) => self.add_movie(movie),
+            Production::SearchedSeries(series) => {
+                self.add_searched_series(&series);
+                return;
+            }
+            Production::Person(_) => unreachable!(),
+        }
+        self.toasts.add(Toast {
+            text: format!("Added {title} to your library").into(),
+            kind: ToastKind::Success,
+            options: ToastOptions::default()
+                .duration_in_seconds(2.5)
+                .show_progress(true)
+                .show_icon(true),
+        });
+    }
+
+    fn refresh_library(&mut self) {
+        if self.refresh.is_running() {
+            return;
+        }
+        let movies = self.user_movies.iter().map(|user_movie| (user_movie.movie.id, user_movie.movie.title.clone())).collect();
+        let series = self.user_series.iter().map(|user_series| (user_series.series.id, user_series.series.name.clone())).collect();
+        self.refresh = self.movie_db.refresh_library(movies, series, self.refreshed_at);
+    }
+
+    // Only TMDB's fields are replaced, ratings, notes and the rest of the user's data stay as they are
+    fn poll_refresh(&mut self) {
+        let Some(outcome) = self.refresh.poll_owned() else {
+            return;
+        };
+        let checked = outcome.refreshed.len();
+        let mut report = Vec::new();
+        for refreshed in outcome.refreshed {
+            match refreshed {
+                Refreshed::Movie(movie) => {
+                    let Some(user_movie) = self.user_movies.iter_mut().find(|user_movie| user_movie.movie.id == movie.id) else {
+                        continue;
+                    };
+                    let changes = movie_changes(&user_movie.movie, &movie);
+                    if !changes.is_empty() {
+                        report.push((movie.title.clone(), changes));
+                    }
+                    user_movie.movie = movie;
+                }
+                Refreshed::Series(series) => {
+                    let Some(user_series) = self.user_series.iter_mut().find(|user_series| user_series.series.id == series.id) else {
+                        continue;
+                    };
+                    let mut changes = series_changes(&user_series.series, &series);
+                    let title = series.name.clone();
+                    let grown_notes = user_series.update_series(series);
+                    if grown_notes > 0 {
+                        changes.push(format!("Made room in {grown_notes} season notes"));
+                    }
+                    if !changes.is_empty() {
+                        report.push((title, changes));
+                    }
+                }
+            }
+        }
+
+        // Entries that failed would be skipped as unchanged next time
+        if outcome.whole_library && outcome.failed.is_empty() {
+            self.refreshed_at = Some(outcome.started_at);
+        }
+        let mut text = format!("Refreshed {checked} titles, {} changed", report.len());
+        if outcome.skipped > 0 {
+            text.push_str(&format!(", {} unchanged on TMDB", outcome.skipped));
+        }
+        self.toasts.add(Toast {
+            text: text.into(),
+            kind: ToastKind::Success,
+            options: ToastOptions::default()
+                .duration_in_seconds(3.5)
+                .show_progress(true)
+                .show_icon(true),
+        });
+        if !outcome.failed.is_empty() {
+            self.toasts.add(error_toast(format!("Failed to refresh {} titles", outcome.failed.len())));
+        }
+        let failures = outcome.failed.iter().map(|(title, err)| format!("{title}: {err}")).collect();
+        self.refresh_report.set_report(report, failures);
         self.central_list_reload();
     }
 
+    fn poll_genres(&mut self) {
+        match self.genres.poll() {
+            Ok(true) => self.central_list_reload(),
+            Ok(false) => {}
+            Err(err) => {
+                self.toasts.add(error_toast(format!("Failed to fetch genres: {err}")));
+            }
+        }
+    }
+
+    fn fetch_certification(&mut self, entry: EntryType) {
+        let job = match entry {
+            EntryType::Movie(id) => self.movie_db.get_movie_release_dates(id),
+            EntryType::Series(id) => self.movie_db.get_series_content_ratings(id),
+            EntryType::None => return,
+        };
+        self.certification_jobs.push((entry, job));
+    }
+
+    // Age ratings differ between countries, they are fetched again for the new region
+    fn forget_certifications(&mut self) {
+        self.certification_jobs.clear();
+        self.central_filtering.certification = None;
+        for user_movie in &mut self.user_movies {
+            user_movie.certification = None;
+        }
+        for user_series in &mut self.user_series {
+            user_series.certification = None;
+        }
+        for entry in &mut self.central_user_list {
+            entry.certification = None;
+        }
+        self.central_draw_list_update();
+    }
+
+    // An invalid code is put back. Offers and available services are region specific
+    fn commit_region(&mut self) {
+        let region = self.config.region.trim().to_uppercase();
+        if !is_region_code(&region) {
+            self.config.region = self.movie_db.region.clone();
+            return
+        }
+        self.config.region = region.clone();
+        if region == self.movie_db.region {
+            return
+        }
+        self.movie_db.region = region;
+        self.watch_providers.clear();
+        self.region_providers = Job::Empty;
+        self.forget_certifications();
+    }
+
+    // Invalid tags are put back, the fallback may be left empty.
+    // Responses are cached per language, already loaded results keep theirs
+    fn commit_languages(&mut self) {
+        let language = self.config.language.trim();
+        let fallback = self.config.fallback_language.trim();
+        let language = if is_language_tag(language) { language.to_string() } else { self.movie_db.language.clone() };
+        let fallback = if fallback.is_empty() || is_language_tag(fallback) {
+            fallback.to_string()
+        } else {
+            self.movie_db.fallback_language.clone()
+        };
+        self.config.language = language.clone();
+        self.config.fallback_language = fallback.clone();
+        if language == self.movie_db.language && fallback == self.movie_db.fallback_language {
+            return
+        }
+        self.movie_db.language = language;
+        self.movie_db.fallback_language = fallback;
+        self.genres.load(&self.movie_db);
+    }
+
+    // Libraries saved before certifications were stored have none
+    fn fetch_missing_certifications(&mut self) {
+        let missing: Vec<EntryType> = self
+            .central_user_list
+            .iter()
+            .filter(|entry| entry.certification.is_none())
+            .map(|entry| entry.production_id)
+            .filter(|id| !self.certification_jobs.iter().any(|(pending, _)| ListEntry::is_same_id(pending, id)))
+            .collect();
+        for entry in missing {
+            self.fetch_certification(entry);
+        }
+    }
+
+    fn poll_certifications(&mut self) {
+        let mut updated = false;
+        let mut i = 0;
+        while i < self.certification_jobs.len() {
+            let Some(result) = self.certification_jobs[i].1.poll_owned() else {
+                i += 1;
+                continue;
+            };
+            let (entry, _) = self.certification_jobs.swap_remove(i);
+            let releases = match result {
+                Ok(releases) => releases,
+                Err(err) => {
+                    eprintln!("ERROR: Failed to fetch the age rating: {err}");
+                    continue;
+                }
+            };
+
+            let certification = find_country(&releases, &self.config.region)
+                .map(|release| release.certification.clone())
+                .unwrap_or_default();
+            match entry {
+                EntryType::Movie(id) => {
+                    if let Some(user_movie) = self.user_movies.iter_mut().find(|user_movie| user_movie.movie.id == id) {
+                        user_movie.certification = Some(certification.clone());
+                    }
+                }
+                EntryType::Series(id) => {
+                    if let Some(user_series) = self.user_series.iter_mut().find(|user_series| user_series.series.id == id) {
+                        user_series.certification = Some(certification.clone());
+                    }
+                }
+                EntryType::None => {}
+            }
+            for list_entry in self.central_user_list.iter_mut().filter(|list_entry| list_entry.is_selected(&entry)) {
+                list_entry.certification = Some(certification.clone());
+            }
+            updated = true;
+        }
+
+        if updated {
+            self.central_draw_list_update();
+        }
+    }
+
+    // Starts provider lookups for the whole library, needed by the "my services" filter
+    fn fetch_library_providers(&mut self) {
+        let keys = self.central_user_list.iter().filter_map(|entry| match entry.production_id {
+            EntryType::Movie(id) => Some((false, id)),
+            EntryType::Series(id) => Some((true, id)),
+            EntryType::None => None,
+        });
+        for key in keys {
+            self.watch_providers
+                .entry(key)
+                .or_insert_with(|| self.movie_db.get_watch_providers(key.0, key.1));
+        }
+    }
+
+    fn poll_watch_providers(&mut self) {
+        let mut finished = false;
+        for job in self.watch_providers.values_mut() {
+            if job.is_running() {
+                job.poll();
+                finished |= !job.is_running();
+            }
+        }
+        if finished && self.central_filtering.filter_my_services {
+            self.central_draw_list_update();
+        }
+    }
+
+    fn poll_poster_downloads(&mut self) {
+        let mut i = 0;
+        while i < self.poster_downloads.len() {
+            let Some(outcome) = self.poster_downloads[i].poll_owned() else {
+                i += 1;
+                continue;
+            };
+            self.poster_downloads.remove(i);
+            match outcome {
+                Ok(bytes) => {
+                    self.toasts.add(Toast {
+                        text: format!("Downloaded poster ({bytes} bytes)").into(),
+                        kind: ToastKind::Success,
+                        options: ToastOptions::default()
+                            .duration_in_seconds(2.5)
+                            .show_progress(true)
+                            .show_icon(true),
+                    });
+                }
+                Err(err) => {
+                    self.toasts.add(error_toast(format!("Failed to download poster: {err}")));
+                }
+            }
+        }
+    }
+
     pub fn render(&mut self, ctx: &egui::Context) {
-        self.series_view.draw(ctx, &self.movie_db);
-        self.movie_view.draw(ctx, &self.movie_db);
+        self.poll_poster_downloads();
+        self.poll_watch_providers();
+        self.poll_certifications();
+        self.poll_genres();
+        self.poll_image_configuration();
+        self.poll_refresh();
+        self.poll_link_lookup();
+        self.poll_pending_actions();
+        // Results aren't visible until applied, so keep checking while anything is pending
+        if !self.pending_actions.is_empty() {
+            ctx.request_repaint_after(Duration::from_millis(100));
+        }
+        self.handle_dropped_files(ctx);
+        self.movie_db.image_scale = ctx.pixels_per_point();
+        let library = LibraryIds::new(&self.user_movies, &self.user_series);
+        let actions = [
+            self.series_view.draw(ctx, &self.movie_db, &library, &mut self.toasts),
+            self.movie_view.draw(ctx, &self.movie_db, &library, &mut self.toasts),
+            self.person_view.draw(ctx, &self.movie_db, &library, &mut self.toasts),
+            self.collection_view.draw(ctx, &self.movie_db, &library, &mut self.toasts),
+            self.episode_view.draw(ctx, &self.movie_db, &library, &mut self.toasts),
+            self.artwork_view.draw(ctx, &self.movie_db, &library, &mut self.toasts),
+        ];
+        for action in actions.into_iter().flatten() {
+            self.handle_view_action(action);
+        }
         self.trailers_view.draw(ctx);
         self.license_view.draw(ctx);
+        self.refresh_report.draw(ctx);
+        self.tasks_view.draw(ctx);
+        self.discover_window(ctx);
+        self.related_window(ctx);
 
         // Show all toasts
         self.toasts.show(ctx);
@@ -502,6 +1224,7 @@ impl MovieApp {
 
 const MOVIE_URL: &str = "https://www.themoviedb.org/movie/";
 const TV_URL: &str = "https://www.themoviedb.org/tv/";
+const PERSON_URL: &str = "https://www.themoviedb.org/person/";
 
 impl MovieApp {
     fn left_panel(&mut self, ctx: &egui::Context) {
@@ -512,40 +1235,88 @@ impl MovieApp {
             ui.heading("Find a production");
             ui.separator();
 
-            ui.with_layout(Layout::top_down_justified(Align::Min), |ui| {
-                let search_field = egui::TextEdit::singleline(&mut self.search)
-                    .min_size(Vec2::new(20.0, 0.0))
-                    .hint_text("Search");
-
-                let response = ui.add(search_field);
-                let pressed_enter = ui.input(|i| i.key_pressed(egui::Key::Enter));
-
-                if response.lost_focus() && pressed_enter {
-                    self.fetch_productions_job = self.movie_db.search_production(self.search.clone());
-                    search_triggered = true;
+            ui.horizontal_wrapped(|ui| {
+                ui.selectable_value(&mut self.left_tab, LeftTab::Search, "Search");
+                for feed in Feed::ALL {
+                    ui.selectable_value(&mut self.left_tab, LeftTab::Feed(feed), feed.label());
                 }
             });
-
-            ui.add_space(5.0);
-
-            ui.checkbox(&mut self.show_adult_content, "Show adult content");
             ui.separator();
 
-            self.production_grid(ui, search_triggered);
-            ui.separator();
-        });
-    }
+            if let LeftTab::Feed(feed) = self.left_tab {
+                self.feed_header(ui, feed);
+                ui.separator();
+                self.production_grid(ui, false);
+                return;
+            }
 
-    fn central_panel(&mut self, ctx: &egui::Context) {
-        // NOTE:
-        //     We could also add option exclude or find productions by keyword.
-        //     Searching by both production title and keywords could also be interesting.
-        let center = egui::CentralPanel::default();
-        center.show(ctx, |ui| {
             ui.horizontal(|ui| {
-                ui.heading("Your movies!");
+                egui::ComboBox::from_id_source("search_mode")
+                    .selected_text(self.search_mode.label())
+                    .show_ui(ui, |ui| {
+                        for mode in SearchMode::ALL {
+                            ui.selectable_value(&mut self.search_mode, mode, mode.label());
+                        }
+                    });
 
-                ui.with_layout(egui::Layout::right_to_left(Align::Max), |ui| {
+                ui.with_layout(Layout::top_down_justified(Align::Min), |ui| {
+                    let search_field = egui::TextEdit::singleline(&mut self.search)
+                        .min_size(Vec2::new(20.0, 0.0))
+                        .hint_text("Search");
+
+                    let response = ui.add(search_field);
+                    let pressed_enter = ui.input(|i| i.key_pressed(egui::Key::Enter));
+
+                    if response.lost_focus() && pressed_enter {
+                        if let Some(link) = parse_external_link(&self.search) {
+                            self.look_up_link(link, self.search.trim().to_string());
+                            return;
+                        }
+                        let params = self.search_options.to_params(&self.search, self.show_adult_content);
+                        self.search_results.load(Listing::Search(self.search_mode, params), &self.movie_db);
+                        search_triggered = true;
+                    }
+                });
+            });
+
+            ui.add_space(5.0);
+
+            if ui.checkbox(&mut self.show_adult_content, "Show adult content").changed() {
+                self.config.include_adult = self.show_adult_content;
+            }
+
+            ui.collapsing("Search options", |ui| {
+                egui::Grid::new("search_options_grid").num_columns(2).show(ui, |ui| {
+                    ui.label("Language");
+                    ui.add(egui::TextEdit::singleline(&mut self.search_options.language).hint_text("en-US"));
+                    ui.end_row();
+
+                    ui.label("Region");
+                    ui.add(egui::TextEdit::singleline(&mut self.search_options.region).hint_text("US"));
+                    ui.end_row();
+
+                    ui.label("Year");
+                    ui.add(egui::TextEdit::singleline(&mut self.search_options.year).hint_text("Any"));
+                    ui.end_row();
+                });
+            });
+            ui.separator();
+
+            self.production_grid(ui, search_triggered);
+            ui.separator();
+        });
+    }
+
+    fn central_panel(&mut self, ctx: &egui::Context) {
+        // NOTE:
+        //     We could also add option exclude or find productions by keyword.
+        //     Searching by both production title and keywords could also be interesting.
+        let center = egui::CentralPanel::default();
+        center.show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.heading("Your movies!");
+
+                ui.with_layout(egui::Layout::right_to_left(Align::Max), |ui| {
                     if ui.button("U").on_hover_text("User defined ordering").clicked() {
                         self.central_ordering = ListOrdering::UserDefined;
                         self.central_draw_list_update();
@@ -577,6 +1348,80 @@ impl MovieApp {
                         self.central_draw_list_update();
                     }
 
+                    let selected = self.central_filtering.certification.as_deref().unwrap_or("Any rating").to_string();
+                    egui::ComboBox::from_id_source("certification_filter")
+                        .selected_text(selected)
+                        .show_ui(ui, |ui| {
+                            self.fetch_missing_certifications();
+                            let mut certifications: Vec<String> = self
+                                .central_user_list
+                                .iter()
+                                .filter_map(|entry| entry.certification.clone())
+                                .filter(|certification| !certification.is_empty())
+                                .collect();
+                            certifications.sort();
+                            certifications.dedup();
+
+                            let before = self.central_filtering.certification.clone();
+                            ui.selectable_value(&mut self.central_filtering.certification, None, "Any rating");
+                            for certification in certifications {
+                                let label = certification.clone();
+                                ui.selectable_value(&mut self.central_filtering.certification, Some(certification), label);
+                            }
+                            if before != self.central_filtering.certification {
+                                self.central_draw_list_update();
+                            }
+                        })
+                        .response
+                        .on_hover_text(format!("Age rating in {}", self.config.region));
+
+                    let selected = self.central_filtering.genre.as_deref().unwrap_or("Any genre").to_string();
+                    egui::ComboBox::from_id_source("genre_filter")
+                        .selected_text(selected)
+                        .show_ui(ui, |ui| {
+                            let mut genres: Vec<String> = self
+                                .central_user_list
+                                .iter()
+                                .flat_map(|entry| entry.genres.iter().cloned())
+                                .collect();
+                            genres.sort();
+                            genres.dedup();
+
+                            let before = self.central_filtering.genre.clone();
+                            ui.selectable_value(&mut self.central_filtering.genre, None, "Any genre");
+                            for genre in genres {
+                                let label = genre.clone();
+                                ui.selectable_value(&mut self.central_filtering.genre, Some(genre), label);
+                            }
+                            if before != self.central_filtering.genre {
+                                self.central_draw_list_update();
+                            }
+                        });
+
+                    if ui.selectable_label(self.group_by_genre, "G").on_hover_text("Group by genre").clicked() {
+                        self.group_by_genre = !self.group_by_genre;
+                        self.central_draw_list_update();
+                    }
+
+                    let titles_hint = if self.original_titles {
+                        "Showing original titles"
+                    } else {
+                        "Showing titles in the metadata language"
+                    };
+                    if ui.selectable_label(self.original_titles, "Aa").on_hover_text(titles_hint).clicked() {
+                        self.original_titles = !self.original_titles;
+                        self.central_list_reload();
+                    }
+
+                    if ui.button("S").on_hover_text("Available on my services").clicked() {
+                        self.central_filtering.filter_my_services = !self.central_filtering.filter_my_services;
+                        let filter = &self.central_filtering;
+                        if filter.filter_my_services && self.config.subscribed_providers.is_empty() {
+                            self.toasts.add(error_toast("No services selected, pick them in Settings > My services"));
+                        }
+                        self.central_draw_list_update();
+                    }
+
                     if ui.button("TW").on_hover_text("To Watch").clicked() {
                         self.central_filtering.filter_watched = false;
                         self.central_filtering.filter_to_watch = !self.central_filtering.filter_to_watch;
@@ -616,6 +1461,14 @@ impl MovieApp {
                 break;
             }
 
+            if self.group_by_genre {
+                let group = self.central_draw_list[i].genres.first();
+                if i == 0 || self.central_draw_list[i - 1].genres.first() != group {
+                    ui.add_space(4.0);
+                    ui.strong(group.map_or("No genre", String::as_str));
+                }
+            }
+
             let entry_size = Vec2::new(ui.available_width(), 32.0);
             let (entry_rect, entry_response) = ui.allocate_exact_size(entry_size, egui::Sense::click());
 
@@ -657,7 +1510,7 @@ impl MovieApp {
             let poster_rect = Rect::from_min_size(poster_pos, poster_size);
 
             let poster = if let Some(ref path) = self.central_draw_list[i].poster_path {
-                let image_url = TheMovieDB::get_full_poster_url(path, Width::W300);
+                let image_url = self.movie_db.get_image_url(path, ImageKind::Poster, 20.0);
                 egui::Image::new(image_url)
             } else {
                 let image_source = include_image!("../res/no_image.png");
@@ -865,31 +1718,63 @@ impl MovieApp {
                 let Some(user_movie) = self.user_movies.get_mut(index) else {
                     return;
                 };
+                let poster = user_movie.poster_path().cloned();
                 let movie = &user_movie.movie;
                 ui.heading(&movie.title);
 
                 ui.horizontal(|ui| {
-                    if let Some(poster) = &movie.poster_path {
-                        let image_url = TheMovieDB::get_full_poster_url(poster, Width::W300);
+                    if let Some(poster) = &poster {
+                        let image_url = self.movie_db.get_image_url(poster, ImageKind::Poster, 100.0);
                         let image = egui::Image::new(image_url);
                         ui.add_sized([100.0, 100.0], image);
                     }
-                    ui.checkbox(&mut user_movie.watched, "Watched");
+                    ui.vertical(|ui| {
+                        ui.checkbox(&mut user_movie.watched, "Watched");
+                        if ui.button("Choose artwork").clicked() {
+                            self.artwork_view.set_target(false, movie.id, movie.title.clone(), &self.movie_db);
+                        }
+                        if ui.button("Download poster").clicked() {
+                            if let Some(poster) = &poster {
+                                let resource = self.movie_db.get_original_image_url(poster);
+                                self.poster_downloads.push(self.movie_db.download_poster(&resource, &poster[1..]));
+                            }
+                        }
+                        let refresh_button = egui::Button::new("Refresh metadata");
+                        if ui.add_enabled(!self.refresh.is_running(), refresh_button).clicked() {
+                            self.refresh = self.movie_db.refresh_entry(false, movie.id, movie.title.clone());
+                        }
+                    });
                 });
             } else {
                 let Some(user_series) = self.user_series.get_mut(index) else {
                     return;
                 };
+                let poster = user_series.poster_path().cloned();
                 let series = &user_series.series;
                 ui.heading(&series.name);
 
                 ui.horizontal(|ui| {
-                    if let Some(poster) = &series.poster_path {
-                        let image_url = TheMovieDB::get_full_poster_url(poster, Width::W300);
+                    if let Some(poster) = &poster {
+                        let image_url = self.movie_db.get_image_url(poster, ImageKind::Poster, 100.0);
                         let image = egui::Image::new(image_url);
                         ui.add_sized([100.0, 100.0], image);
                     }
-                    ui.checkbox(&mut user_series.watched, "Watched");
+                    ui.vertical(|ui| {
+                        ui.checkbox(&mut user_series.watched, "Watched");
+                        if ui.button("Choose artwork").clicked() {
+                            self.artwork_view.set_target(true, series.id, series.name.clone(), &self.movie_db);
+                        }
+                        if ui.button("Download poster").clicked() {
+                            if let Some(poster) = &poster {
+                                let resource = self.movie_db.get_original_image_url(poster);
+                                self.poster_downloads.push(self.movie_db.download_poster(&resource, &poster[1..]));
+                            }
+                        }
+                        let refresh_button = egui::Button::new("Refresh metadata");
+                        if ui.add_enabled(!self.refresh.is_running(), refresh_button).clicked() {
+                            self.refresh = self.movie_db.refresh_entry(true, series.id, series.name.clone());
+                        }
+                    });
                 });
 
                 let display = if self.selection.season.is_some() {
@@ -938,6 +1823,17 @@ impl MovieApp {
                 }
             }
 
+            let key = match self.selected_entry {
+                EntryType::Movie(id) => (false, id),
+                EntryType::Series(id) => (true, id),
+                EntryType::None => unreachable!("Returned early when nothing is selected"),
+            };
+            let providers = self
+                .watch_providers
+                .entry(key)
+                .or_insert_with(|| self.movie_db.get_watch_providers(key.0, key.1));
+            draw_watch_providers(ui, "right_panel_providers", providers, &self.movie_db);
+
             ui.separator();
             ui.add_space(8.0);
 
@@ -985,10 +1881,22 @@ impl MovieApp {
                     let season_num = self.selection.season();
 
                     // NOTE: Format every frame. BAD! We need to cache it.
-                    ui.label(format!("S{season_num} E{episode_num} notes:"));
+                    let show_details = ui.horizontal(|ui| {
+                        ui.label(format!("S{season_num} E{episode_num} notes:"));
+                        ui.button("Episode details").clicked()
+                    }).inner;
+                    if show_details {
+                        let (series_id, name) = (user_series.series.id, user_series.series.name.clone());
+                        let note = self.episode_note(series_id, season_num, episode_num);
+                        self.episode_view.set_episode(series_id, &name, season_num, episode_num, note, &self.movie_db);
+                    }
+                    let user_series = self.user_series.get_mut(self.selection.index()).unwrap();
                     ui.with_layout(Layout::top_down_justified(Align::Min), |ui| {
-                        let season_notes = user_series.season_note(season_num);
-                        ui.text_edit_multiline(season_notes.episode_note(episode_num));
+                        let series_id = user_series.series.id;
+                        let note = user_series.season_note(season_num).episode_note(episode_num);
+                        if ui.text_edit_multiline(note).changed() {
+                            self.episode_view.update_note(series_id, season_num, episode_num, note);
+                        }
                     });
                     return;
                 }
@@ -1062,6 +1970,15 @@ impl MovieApp {
                         }
                     });
 
+                    let refresh_button = ui
+                        .add_enabled(!self.refresh.is_running(), egui::Button::new("Refresh library metadata"))
+                        .on_hover_text("Fetch current metadata for every title, notes and ratings are kept")
+                        .on_disabled_hover_text("Refreshing...");
+                    if refresh_button.clicked() {
+                        self.refresh_library();
+                        ui.close_menu();
+                    }
+
                     if ui.button("Ensure data integrity").clicked() {
                         self.fix_data_integrity();
                     }
@@ -1083,6 +2000,15 @@ impl MovieApp {
 
                     if ui.button("Load config").clicked() {
                         self.config = Config::load("res/config.json");
+                        let region_changed = self.movie_db.region != self.config.region;
+                        self.movie_db.apply_config(&self.config);
+                        self.image_configuration = self.movie_db.get_image_configuration();
+                        self.genres.load(&self.movie_db);
+                        self.watch_providers.clear();
+                        self.region_providers = Job::Empty;
+                        if region_changed {
+                            self.forget_certifications();
+                        }
                     }
                 });
 
@@ -1096,6 +2022,11 @@ impl MovieApp {
                     if ui.button("PPP -0.01").clicked() {
                         ctx.set_pixels_per_point(ctx.pixels_per_point() - 0.01);
                     }
+
+                    if ui.button("Tasks").clicked() {
+                        self.tasks_view.is_open = true;
+                        ui.close_menu();
+                    }
                 });
 
                 ui.menu_button("Settings", |ui| {
@@ -1122,6 +2053,59 @@ impl MovieApp {
                         ui.text_edit_singleline(&mut self.config.browser_name);
                     });
 
+                    ui.menu_button("Set watch region", |ui| {
+                        let region = egui::TextEdit::singleline(&mut self.config.region).hint_text("US");
+                        if ui.add(region).lost_focus() {
+                            self.commit_region();
+                        }
+                    });
+
+                    ui.menu_button("Metadata language", |ui| {
+                        ui.label("Language");
+                        let language = egui::TextEdit::singleline(&mut self.config.language).hint_text("en-US");
+                        let mut committed = ui.add(language).lost_focus();
+                        ui.label("Fallback for untranslated text");
+                        let fallback = egui::TextEdit::singleline(&mut self.config.fallback_language).hint_text("en-US");
+                        committed |= ui.add(fallback).lost_focus();
+                        if committed {
+                            self.commit_languages();
+                        }
+                    });
+
+                    ui.menu_button("My services", |ui| {
+                        if matches!(self.region_providers, Job::Empty) {
+                            self.region_providers = self.movie_db.get_region_providers();
+                        }
+                        match self.region_providers.poll() {
+                            None => {
+                                ui.spinner();
+                            }
+                            Some(Err(err)) => {
+                                ui.label(format!("Failed to fetch services: {err}"));
+                            }
+                            Some(Ok(providers)) => {
+                                let mut changed = false;
+                                egui::ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                                    for provider in providers {
+                                        let subscribed = &mut self.config.subscribed_providers;
+                                        let mut checked = subscribed.contains(&provider.provider_id);
+                                        if ui.checkbox(&mut checked, &provider.provider_name).changed() {
+                                            if checked {
+                                                subscribed.push(provider.provider_id);
+                                            } else {
+                                                subscribed.retain(|id| *id != provider.provider_id);
+                                            }
+                                            changed = true;
+                                        }
+                                    }
+                                });
+                                if changed && self.central_filtering.filter_my_services {
+                                    self.central_draw_list_update();
+                                }
+                            }
+                        }
+                    });
+
                     let autosave_label = if self.config.autosave {
                         "Disable auto-save"
                     } else {
@@ -1138,6 +2122,27 @@ impl MovieApp {
                     };
                     if ui.button(caching_label).clicked() {
                         self.config.enable_cache = !self.config.enable_cache;
+                        self.movie_db.use_cache = self.config.enable_cache;
+                    }
+
+                    let stats = self.movie_db.cache().stats();
+                    let clear_label = format!("Clear cache ({} entries, {:.1} MB)", stats.entries, stats.size as f64 / (1024.0 * 1024.0));
+                    if ui.button(clear_label).clicked() {
+                        match self.movie_db.cache().clear() {
+                            Ok(_) => {
+                                self.toasts.add(Toast {
+                                    text: "Cleared cache".into(),
+                                    kind: ToastKind::Success,
+                                    options: ToastOptions::default()
+                                        .duration_in_seconds(2.5)
+                                        .show_progress(true)
+                                        .show_icon(true),
+                                });
+                            }
+                            Err(msg) => {
+                                self.toasts.add(error_toast(format!("Failed to clear cache: {msg}")));
+                            }
+                        }
                     }
 
                     if ui.button("Sync").clicked() {
@@ -1145,10 +2150,23 @@ impl MovieApp {
                     }
                 });
 
+                if ui.button("Discover").clicked() {
+                    self.discover_options.is_open = true;
+                }
+
                 ui.menu_button("About", |_| {});
                 ui.menu_button("License", |_| {
                     self.license_view.is_open = true;
                 });
+
+                ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                    let queued = self.movie_db.queued_requests();
+                    if self.movie_db.is_rate_limited() {
+                        ui.colored_label(egui::Color32::YELLOW, format!("Rate limited, {queued} requests waiting"));
+                    } else if queued > 0 {
+                        ui.label(format!("{queued} requests queued"));
+                    }
+                });
             });
         });
     }
@@ -1160,7 +2178,7 @@ impl MovieApp {
 
         ui.horizontal(|ui| {
             let image = if let Some(poster) = &movie.poster_path {
-                let image_url = TheMovieDB::get_full_poster_url(poster, Width::W300);
+                let image_url = self.movie_db.get_image_url(poster, ImageKind::Poster, 60.0);
                 egui::Image::new(image_url)
             } else {
                 egui::Image::new(include_image!("../res/no_image.png"))
@@ -1176,6 +2194,9 @@ impl MovieApp {
                 //change name?: xpanded view, about, more, view seasons, view more, view details,
                 if ui.button("More details").clicked() {
                     self.movie_view.set_movie(movie.clone(), &self.movie_db);
+                    if let Some(user_movie) = self.user_movies.iter().find(|user_movie| user_movie.movie.id == movie.id) {
+                        self.movie_view.set_header(movie.id, user_movie.custom_backdrop.clone());
+                    }
                     ui.close_menu();
                 }
 
@@ -1187,25 +2208,41 @@ impl MovieApp {
                 }
 
                 if ui.button("Open in IMDB").clicked() {
-                    let url = self.movie_db.get_imdb_url_movie(&movie.title, movie.id);
-                    let browser = &self.config.browser_name;
-                    let _ = open::with_in_background(url, browser);
+                    self.movie_details_action(movie, EntryAction::OpenImdb);
+                    ui.close_menu();
                 }
 
-                if ui.button("Fetch keywords").clicked() {
-                    let keywords = self.movie_db.get_keywords_movie(movie.id);
-                    println!("{:?}", keywords)
+                if ui.button("Show keywords").clicked() {
+                    self.movie_details_action(movie, EntryAction::ShowKeywords);
+                    ui.close_menu();
                 }
 
                 if ui.button("Fetch trailers").clicked() {
-                    let trailers = self.movie_db.get_movie_trailers(movie.id);
-                    self.trailers_view.set_content(movie.title.to_owned(), trailers);
+                    self.movie_details_action(movie, EntryAction::ShowTrailers);
+                    ui.close_menu();
+                }
+
+                if ui.button("Recommended titles").clicked() {
+                    self.open_related(Related::Recommendations, false, movie.id, &movie.title);
+                    ui.close_menu();
                 }
 
-                if ui.button("Download poster").clicked() && movie.poster_path.is_some() {
-                    let poster = movie.poster_path.as_ref().unwrap();
-                    let resource = TheMovieDB::get_full_poster_url(poster, Width::Original);
-                    self.movie_db.download_poster(&resource, &poster[1..]);
+                if ui.button("Similar titles").clicked() {
+                    self.open_related(Related::Similar, false, movie.id, &movie.title);
+                    ui.close_menu();
+                }
+
+                if ui.button("Browse artwork").clicked() {
+                    self.artwork_view.set_target(false, movie.id, movie.title.clone(), &self.movie_db);
+                    ui.close_menu();
+                }
+
+                if ui.button("Download poster").clicked() {
+                    if let Some(poster) = &movie.poster_path {
+                        let resource = self.movie_db.get_original_image_url(poster);
+                        let job = self.movie_db.download_poster(&resource, &poster[1..]);
+                        self.poster_downloads.push(job);
+                    }
                 }
 
                 if ui.button("Close menu").clicked() {
@@ -1215,7 +2252,15 @@ impl MovieApp {
 
             ui.vertical(|ui| {
                 ui.add_space(10.0);
-                ui.heading(&movie.title);
+                ui.horizontal(|ui| {
+                    ui.heading(&movie.title);
+                    if self.is_entry_pending(false, movie.id) {
+                        ui.spinner();
+                    }
+                });
+                if self.user_movies.iter().any(|user_movie| user_movie.movie.id == movie.id) {
+                    ui.label("\u{2714} In your library");
+                }
                 ui.add_space(8.0);
                 ui.label(format!("Rating: {} / 10", movie.vote_average));
                 ui.add_space(4.0);
@@ -1251,7 +2296,7 @@ impl MovieApp {
 
         ui.horizontal(|ui| {
             let image = if let Some(poster) = &series.poster_path {
-                let image_url = TheMovieDB::get_full_poster_url(poster, Width::W300);
+                let image_url = self.movie_db.get_image_url(poster, ImageKind::Poster, 60.0);
                 egui::Image::new(image_url)
             } else {
                 egui::Image::new(include_image!("../res/no_image.png"))
@@ -1261,9 +2306,7 @@ impl MovieApp {
             let poster = ui.add_sized([60.0, 100.0], image).interact(egui::Sense::click());
             poster.context_menu(|ui| {
                 if ui.button("Add series").clicked() {
-                    let details = self.movie_db.get_series_details_now(series.id);
-                    let new_data = Series::from(series, details);
-                    self.add_series(new_data);
+                    self.add_searched_series(series);
                     ui.close_menu()
                 }
 
@@ -1280,25 +2323,41 @@ impl MovieApp {
                 }
 
                 if ui.button("Open in IMDB").clicked() {
-                    let url = self.movie_db.get_imdb_url_series(&series.name, series.id);
-                    let browser = &self.config.browser_name;
-                    let _ = open::with_in_background(url, browser);
+                    self.series_details_action(series, EntryAction::OpenImdb);
+                    ui.close_menu();
+                }
+
+                if ui.button("Show keywords").clicked() {
+                    self.series_details_action(series, EntryAction::ShowKeywords);
+                    ui.close_menu();
                 }
 
                 if ui.button("Fetch trailers").clicked() {
-                    let trailers = self.movie_db.get_series_trailers(series.id);
-                    self.trailers_view.set_content(series.name.to_owned(), trailers);
+                    self.series_details_action(series, EntryAction::ShowTrailers);
+                    ui.close_menu();
+                }
+
+                if ui.button("Recommended titles").clicked() {
+                    self.open_related(Related::Recommendations, true, series.id, &series.name);
+                    ui.close_menu();
+                }
+
+                if ui.button("Similar titles").clicked() {
+                    self.open_related(Related::Similar, true, series.id, &series.name);
+                    ui.close_menu();
                 }
 
-                if ui.button("Fetch keywords").clicked() {
-                    let keywords = self.movie_db.get_keywords_series(series.id);
-                    println!("{:?}", keywords)
+                if ui.button("Browse artwork").clicked() {
+                    self.artwork_view.set_target(true, series.id, series.name.clone(), &self.movie_db);
+                    ui.close_menu();
                 }
 
-                if ui.button("Download poster").clicked() && series.poster_path.is_some() {
-                    let poster = series.poster_path.clone().unwrap().to_owned();
-                    let resource = TheMovieDB::get_full_poster_url(&poster, Width::Original);
-                    self.movie_db.download_poster(&resource, &poster[1..]);
+                if ui.button("Download poster").clicked() {
+                    if let Some(poster) = &series.poster_path {
+                        let resource = self.movie_db.get_original_image_url(poster);
+                        let job = self.movie_db.download_poster(&resource, &poster[1..]);
+                        self.poster_downloads.push(job);
+                    }
                 }
 
                 if ui.button("Close menu").clicked() {
@@ -1308,7 +2367,15 @@ impl MovieApp {
 
             ui.vertical(|ui| {
                 ui.add_space(10.0);
-                ui.heading(&series.name);
+                ui.horizontal(|ui| {
+                    ui.heading(&series.name);
+                    if self.is_entry_pending(true, series.id) {
+                        ui.spinner();
+                    }
+                });
+                if self.user_series.iter().any(|user_series| user_series.series.id == series.id) {
+                    ui.label("\u{2714} In your library");
+                }
                 ui.add_space(8.0);
                 ui.label(format!("Rating: {} / 10", series.vote_average));
                 ui.add_space(4.0);
@@ -1337,43 +2404,739 @@ impl MovieApp {
         ui.separator();
     }
 
+    fn draw_person_entry(&mut self, ui: &mut Ui, person: &SearchedPerson) {
+        if person.adult && !self.show_adult_content {
+            return;
+        }
+
+        ui.horizontal(|ui| {
+            let image = if let Some(profile) = &person.profile_path {
+                let image_url = self.movie_db.get_image_url(profile, ImageKind::Profile, 60.0);
+                egui::Image::new(image_url)
+            } else {
+                egui::Image::new(include_image!("../res/no_image.png"))
+            };
+
+            let profile = ui.add_sized([60.0, 100.0], image).interact(egui::Sense::click());
+            if profile.clicked() {
+                self.person_view.set_person(person.id, person.name.clone(), &self.movie_db);
+            }
+            profile.context_menu(|ui| {
+                if ui.button("Show filmography").clicked() {
+                    self.person_view.set_person(person.id, person.name.clone(), &self.movie_db);
+                    ui.close_menu();
+                }
+
+                if ui.button("Open in TMDB").clicked() {
+                    let path = format!("{PERSON_URL}{}", person.id);
+                    let browser = &self.config.browser_name;
+                    let _ = open::with_in_background(path, browser);
+                }
+
+                if ui.button("Close menu").clicked() {
+                    ui.close_menu();
+                }
+            });
+
+            ui.vertical(|ui| {
+                ui.add_space(10.0);
+                ui.heading(&person.name);
+                ui.add_space(8.0);
+                ui.label(format!("Department: {}", person.known_for_department));
+                ui.add_space(4.0);
+                ui.label(format!("Known for: {}", person.known_for_titles()));
+            });
+        });
+
+        ui.separator();
+    }
+
+    fn draw_productions(&mut self, ui: &mut Ui, productions: &[Production]) {
+        for prod in productions {
+            match prod {
+                Production::Movie(ref movie) => self.draw_movie_entry(ui, movie),
+                Production::SearchedSeries(ref series) => self.draw_series_entry(ui, series),
+                Production::Person(ref person) => self.draw_person_entry(ui, person),
+            }
+        }
+    }
+
+    // Results shown under the currently selected left panel tab
+    fn left_results(&mut self) -> &mut PagedProductions {
+        match self.left_tab {
+            LeftTab::Search => &mut self.search_results,
+            LeftTab::Feed(feed) => &mut self.feeds[feed as usize],
+        }
+    }
+
+    // Loads the feed the first time it's shown, tells when it was last refreshed
+    fn feed_header(&mut self, ui: &mut Ui, feed: Feed) {
+        let results = &mut self.feeds[feed as usize];
+        if results.listing.is_none() {
+            results.load(Listing::Feed(feed), &self.movie_db);
+        }
+
+        ui.horizontal(|ui| {
+            let refreshed = match results.loaded_at {
+                Some(loaded_at) => format!("Refreshed {}", format_elapsed(loaded_at.elapsed())),
+                None => "Never refreshed".to_string(),
+            };
+            ui.label(refreshed);
+
+            let refresh = egui::Button::new("Refresh");
+            if ui.add_enabled(!results.job.is_running(), refresh).clicked() {
+                results.load(Listing::Feed(feed), &self.movie_db);
+            }
+        });
+    }
+
     fn production_grid(&mut self, ui: &mut Ui, searched: bool) {
         egui::ScrollArea::vertical().auto_shrink([false, false]).show(ui, |ui| {
             if searched {
                 ui.scroll_to_cursor(Some(Align::Center));
             }
 
-            if let Some(mut productions) = self.fetch_productions_job.poll_owned() {
-                self.sort_productions_by_popularity(&mut productions);
-                let productions: Rc<[Production]> = productions.into();
-                self.search_productions = Some(productions);
+            if let Some(err) = self.left_results().poll() {
+                let failed = match self.left_tab {
+                    LeftTab::Search => "Search failed".to_string(),
+                    LeftTab::Feed(feed) => format!("Failed to load {}", feed.label().to_lowercase()),
+                };
+                self.toasts.add(error_toast(format!("{failed}: {err}")));
             }
 
-            let Some(productions) = self.search_productions.clone() else {
+            let results = self.left_results();
+            let Some(productions) = results.productions.clone() else {
+                if results.job.is_running() {
+                    ui.spinner();
+                }
                 return;
             };
 
-            for prod in &*productions {
-                match prod {
-                    Production::Movie(ref movie) => self.draw_movie_entry(ui, movie),
-                    Production::SearchedSeries(ref series) => self.draw_series_entry(ui, series),
+            self.draw_productions(ui, &productions);
+            let movie_db = &self.movie_db;
+            let results = match self.left_tab {
+                LeftTab::Search => &mut self.search_results,
+                LeftTab::Feed(feed) => &mut self.feeds[feed as usize],
+            };
+            results.footer(ui, movie_db);
+        });
+    }
+
+    fn open_related(&mut self, related: Related, is_tv: bool, id: u32, title: &str) {
+        self.related_title = format!("{} {title}", related.label());
+        self.related_results.load(Listing::Related(related, is_tv, id), &self.movie_db);
+    }
+
+    fn related_window(&mut self, ctx: &egui::Context) {
+        if self.related_results.listing.is_none() {
+            return;
+        }
+
+        let mut is_open = true;
+        let window = egui::Window::new(&self.related_title)
+            .id(egui::Id::new("related_window"))
+            .open(&mut is_open)
+            .default_width(420.0)
+            .default_height(500.0)
+            .resizable(true);
+
+        window.show(ctx, |ui| {
+            if let Some(err) = self.related_results.poll() {
+                self.toasts.add(error_toast(format!("Failed to fetch {}: {err}", self.related_title)));
+            }
+
+            egui::ScrollArea::vertical().auto_shrink([false, false]).show(ui, |ui| {
+                let Some(productions) = self.related_results.productions.clone() else {
+                    if self.related_results.job.is_running() {
+                        ui.spinner();
+                    }
+                    return;
+                };
+
+                if productions.is_empty() {
+                    ui.label("Nothing found");
                 }
+                self.draw_productions(ui, &productions);
+                self.related_results.footer(ui, &self.movie_db);
+            });
+        });
+
+        if !is_open {
+            self.related_results = PagedProductions::new();
+        }
+    }
+
+    fn discover_window(&mut self, ctx: &egui::Context) {
+        if !self.discover_options.is_open {
+            return;
+        }
+
+        let mut is_open = true;
+        let window = egui::Window::new("Discover")
+            .open(&mut is_open)
+            .default_width(420.0)
+            .default_height(500.0)
+            .resizable(true);
+
+        window.show(ctx, |ui| {
+            if self.discover_options.draw(ui, &self.genres) {
+                let is_tv = self.discover_options.is_tv;
+                let params = self.discover_options.to_params(self.show_adult_content);
+                self.discover_results.load(Listing::Discover(is_tv, params), &self.movie_db);
+            }
+            ui.separator();
+
+            if let Some(err) = self.discover_results.poll() {
+                self.toasts.add(error_toast(format!("Discover failed: {err}")));
             }
+
+            egui::ScrollArea::vertical().auto_shrink([false, false]).show(ui, |ui| {
+                let Some(productions) = self.discover_results.productions.clone() else {
+                    if self.discover_results.job.is_running() {
+                        ui.spinner();
+                    }
+                    return;
+                };
+
+                self.draw_productions(ui, &productions);
+                self.discover_results.footer(ui, &self.movie_db);
+            });
         });
+
+        self.discover_options.is_open = is_open;
     }
+}
 
-    fn sort_productions_by_popularity(&mut self, productions: &mut Vec<Production>) {
-        productions.sort_by(|e1, e2| {
-            let pop1 = match e1 {
-                Production::Movie(ref movie1) =>  movie1.popularity,
-                Production::SearchedSeries(ref series1) => series1.popularity
-            };
-            let pop2 = match e2 {
-                Production::Movie(ref movie2) => movie2.popularity,
-                Production::SearchedSeries(ref series2) => series2.popularity
-            };
-            pop2.partial_cmp(&pop1).unwrap()
+#[derive(Copy, Clone, PartialEq)]
+enum SearchMode {
+    All,
+    Movies,
+    Series,
+    People,
+}
+
+impl SearchMode {
+    const ALL: [SearchMode; 4] = [SearchMode::All, SearchMode::Movies, SearchMode::Series, SearchMode::People];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            SearchMode::All => "All",
+            SearchMode::Movies => "Movies",
+            SearchMode::Series => "TV",
+            SearchMode::People => "People",
+        }
+    }
+
+    pub fn search(
+        &self,
+        movie_db: &TheMovieDB,
+        params: &SearchParams,
+        sources: Option<PagedSources>,
+    ) -> Job<TmdbResult<ProductionPage>> {
+        match self {
+            SearchMode::All => movie_db.search_production(params, sources),
+            SearchMode::Movies => movie_db.search_movie(params),
+            SearchMode::Series => movie_db.search_tv(params),
+            SearchMode::People => movie_db.search_person(params),
+        }
+    }
+}
+
+// Raw text of the search option fields in the left panel
+struct SearchOptions {
+    language: String,
+    region: String,
+    year: String,
+}
+
+impl SearchOptions {
+    pub fn new() -> Self {
+        Self {
+            language: String::new(),
+            region: String::new(),
+            year: String::new(),
+        }
+    }
+
+    pub fn to_params(&self, query: &str, include_adult: bool) -> SearchParams {
+        let year = self.year.trim().parse().ok();
+        SearchParams::new(query)
+            .include_adult(include_adult)
+            .language(self.language.as_str())
+            .region(self.region.as_str())
+            .year(year)
+            .first_air_date_year(year)
+    }
+}
+
+// Filters of the Discover window as they are being edited
+struct DiscoverOptions {
+    is_open: bool,
+    is_tv: bool,
+    selected_genres: Vec<u32>,
+    year_from: String,
+    year_to: String,
+    min_vote_average: f32,
+    min_vote_count: u32,
+    runtime_from: String,
+    runtime_to: String,
+    language: String,
+    sort: DiscoverSort,
+}
+
+impl DiscoverOptions {
+    pub fn new() -> Self {
+        Self {
+            is_open: false,
+            is_tv: false,
+            selected_genres: Vec::new(),
+            year_from: String::new(),
+            year_to: String::new(),
+            min_vote_average: 0.0,
+            min_vote_count: 0,
+            runtime_from: String::new(),
+            runtime_to: String::new(),
+            language: String::new(),
+            sort: DiscoverSort::Popularity,
+        }
+    }
+
+    pub fn to_params(&self, include_adult: bool) -> DiscoverParams {
+        let parse = |text: &str| text.trim().parse().ok();
+        let min_vote_average = (self.min_vote_average > 0.0).then_some(self.min_vote_average);
+        let min_vote_count = (self.min_vote_count > 0).then_some(self.min_vote_count);
+        DiscoverParams::new()
+            .include_adult(include_adult)
+            .genres(self.selected_genres.clone())
+            .years(parse(&self.year_from), parse(&self.year_to))
+            .min_vote_average(min_vote_average)
+            .min_vote_count(min_vote_count)
+            .runtime(parse(&self.runtime_from), parse(&self.runtime_to))
+            .original_language(self.language.as_str())
+            .sort(self.sort)
+    }
+
+    // Returns true when the user asked for results
+    pub fn draw(&mut self, ui: &mut Ui, genres: &GenreCatalog) -> bool {
+        ui.horizontal(|ui| {
+            let movies = ui.selectable_value(&mut self.is_tv, false, "Movies");
+            let series = ui.selectable_value(&mut self.is_tv, true, "TV");
+            if movies.changed() || series.changed() {
+                // Movie and TV genres use different ids
+                self.selected_genres.clear();
+            }
+        });
+
+        ui.horizontal_wrapped(|ui| {
+            let list = genres.list(self.is_tv);
+            if list.is_empty() {
+                if genres.is_loading() {
+                    ui.spinner();
+                } else {
+                    ui.label("Genres are unavailable");
+                }
+            }
+            for genre in list {
+                let selected = self.selected_genres.contains(&genre.id);
+                if ui.selectable_label(selected, &genre.name).clicked() {
+                    if selected {
+                        self.selected_genres.retain(|id| *id != genre.id);
+                    } else {
+                        self.selected_genres.push(genre.id);
+                    }
+                }
+            }
+        });
+
+        egui::Grid::new("discover_filters_grid").num_columns(2).show(ui, |ui| {
+            ui.label("Years");
+            ui.horizontal(|ui| {
+                ui.add(egui::TextEdit::singleline(&mut self.year_from).hint_text("From").desired_width(50.0));
+                ui.add(egui::TextEdit::singleline(&mut self.year_to).hint_text("To").desired_width(50.0));
+            });
+            ui.end_row();
+
+            ui.label("Runtime (min)");
+            ui.horizontal(|ui| {
+                ui.add(egui::TextEdit::singleline(&mut self.runtime_from).hint_text("From").desired_width(50.0));
+                ui.add(egui::TextEdit::singleline(&mut self.runtime_to).hint_text("To").desired_width(50.0));
+            });
+            ui.end_row();
+
+            ui.label("Min rating");
+            ui.add(egui::Slider::new(&mut self.min_vote_average, 0.0..=10.0).step_by(0.5));
+            ui.end_row();
+
+            ui.label("Min votes");
+            ui.add(egui::DragValue::new(&mut self.min_vote_count).speed(10).clamp_range(0..=100_000));
+            ui.end_row();
+
+            ui.label("Original language");
+            ui.add(egui::TextEdit::singleline(&mut self.language).hint_text("en").desired_width(50.0));
+            ui.end_row();
+
+            ui.label("Sort by");
+            egui::ComboBox::from_id_source("discover_sort")
+                .selected_text(self.sort.label())
+                .show_ui(ui, |ui| {
+                    for sort in DiscoverSort::ALL {
+                        ui.selectable_value(&mut self.sort, sort, sort.label());
+                    }
+                });
+            ui.end_row();
         });
+
+        ui.button("Discover").clicked()
+    }
+}
+
+#[derive(Copy, Clone, PartialEq)]
+enum LeftTab {
+    Search,
+    Feed(Feed),
+}
+
+// Curated lists shown as left panel tabs, the discriminant indexes MovieApp::feeds
+#[derive(Copy, Clone, PartialEq)]
+enum Feed {
+    TrendingDay,
+    TrendingWeek,
+    PopularMovies,
+    PopularSeries,
+    TopRatedMovies,
+    TopRatedSeries,
+    NowPlaying,
+    Upcoming,
+    AiringToday,
+    OnTheAir,
+}
+
+impl Feed {
+    pub const ALL: [Feed; 10] = [
+        Feed::TrendingDay,
+        Feed::TrendingWeek,
+        Feed::PopularMovies,
+        Feed::PopularSeries,
+        Feed::TopRatedMovies,
+        Feed::TopRatedSeries,
+        Feed::NowPlaying,
+        Feed::Upcoming,
+        Feed::AiringToday,
+        Feed::OnTheAir,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Feed::TrendingDay => "Trending today",
+            Feed::TrendingWeek => "Trending this week",
+            Feed::PopularMovies => "Popular movies",
+            Feed::PopularSeries => "Popular series",
+            Feed::TopRatedMovies => "Top rated movies",
+            Feed::TopRatedSeries => "Top rated series",
+            Feed::NowPlaying => "Now playing",
+            Feed::Upcoming => "Upcoming",
+            Feed::AiringToday => "Airing today",
+            Feed::OnTheAir => "On the air",
+        }
+    }
+
+    pub fn fetch(&self, movie_db: &TheMovieDB, page: u32) -> Job<TmdbResult<ProductionPage>> {
+        match self {
+            Feed::TrendingDay => movie_db.get_trending(false, page),
+            Feed::TrendingWeek => movie_db.get_trending(true, page),
+            Feed::PopularMovies => movie_db.get_popular(false, page),
+            Feed::PopularSeries => movie_db.get_popular(true, page),
+            Feed::TopRatedMovies => movie_db.get_top_rated(false, page),
+            Feed::TopRatedSeries => movie_db.get_top_rated(true, page),
+            Feed::NowPlaying => movie_db.get_now_playing(page),
+            Feed::Upcoming => movie_db.get_upcoming(page),
+            Feed::AiringToday => movie_db.get_airing_today(page),
+            Feed::OnTheAir => movie_db.get_on_the_air(page),
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq)]
+enum Related {
+    Recommendations,
+    Similar,
+}
+
+impl Related {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Related::Recommendations => "Recommended for",
+            Related::Similar => "Similar to",
+        }
+    }
+}
+
+// Context menu actions that need the full details (or, for series being added, the seasons)
+#[derive(Copy, Clone, PartialEq)]
+enum EntryAction {
+    AddSeries,
+    OpenImdb,
+    ShowKeywords,
+    ShowTrailers,
+}
+
+impl EntryAction {
+    fn label(&self) -> &'static str {
+        match self {
+            EntryAction::AddSeries => "add the series",
+            EntryAction::OpenImdb => "fetch the IMDB id",
+            EntryAction::ShowKeywords => "fetch keywords",
+            EntryAction::ShowTrailers => "fetch trailers",
+        }
+    }
+}
+
+enum PendingFetch {
+    Movie(Job<TmdbResult<MovieFullDetails>>),
+    Series(Job<TmdbResult<SeriesFullDetails>>),
+    SeriesToAdd(SearchedSeries, Job<TmdbResult<SeriesDetails>>),
+}
+
+impl PendingFetch {
+    // Whether the result is in
+    fn poll(&mut self) -> bool {
+        match self {
+            PendingFetch::Movie(job) => job.poll().is_some(),
+            PendingFetch::Series(job) => job.poll().is_some(),
+            PendingFetch::SeriesToAdd(_, job) => job.poll().is_some(),
+        }
+    }
+}
+
+struct PendingAction {
+    is_tv: bool,
+    id: u32,
+    title: String,
+    // Every action waiting on the same response, in the order they were picked
+    actions: Vec<EntryAction>,
+    fetch: PendingFetch,
+}
+
+// Where the pages of a PagedProductions come from
+#[derive(Clone)]
+enum Listing {
+    Search(SearchMode, SearchParams),
+    // is_tv, filters
+    Discover(bool, DiscoverParams),
+    Feed(Feed),
+    // is_tv, production id
+    Related(Related, bool, u32),
+}
+
+impl Listing {
+    // sources is what the previous page said about the listings it was merged from
+    pub fn fetch(
+        &self,
+        movie_db: &TheMovieDB,
+        page: u32,
+        sources: Option<PagedSources>,
+    ) -> Job<TmdbResult<ProductionPage>> {
+        match self {
+            Listing::Search(mode, params) => mode.search(movie_db, &params.clone().page(page), sources),
+            Listing::Discover(is_tv, params) => {
+                let params = params.clone().page(page);
+                if *is_tv {
+                    movie_db.discover_tv(&params)
+                } else {
+                    movie_db.discover_movies(&params)
+                }
+            }
+            Listing::Feed(feed) => feed.fetch(movie_db, page),
+            Listing::Related(Related::Recommendations, is_tv, id) => movie_db.get_recommendations(*is_tv, *id, page),
+            Listing::Related(Related::Similar, is_tv, id) => movie_db.get_similar(*is_tv, *id, page),
+        }
+    }
+
+    // Only multi-search mixes movies, series and people by relevance, the other listings already come
+    // in a meaningful order (the Discover sort, top rated first, recommendations first)
+    pub fn sorts_by_popularity(&self) -> bool {
+        matches!(self, Listing::Search(SearchMode::All, _))
+    }
+}
+
+// Movie and TV genre lists, fetched once and used to put names on the genre ids of productions
+struct GenreCatalog {
+    movie: Vec<Genre>,
+    tv: Vec<Genre>,
+    movie_job: Job<TmdbResult<Vec<Genre>>>,
+    tv_job: Job<TmdbResult<Vec<Genre>>>,
+}
+
+impl GenreCatalog {
+    pub fn new() -> Self {
+        Self {
+            movie: Vec::new(),
+            tv: Vec::new(),
+            movie_job: Job::Empty,
+            tv_job: Job::Empty,
+        }
+    }
+
+    // Names depend on the metadata language, reloading keeps the old ones until the new ones arrive
+    pub fn load(&mut self, movie_db: &TheMovieDB) {
+        self.movie_job = movie_db.get_genres(false);
+        self.tv_job = movie_db.get_genres(true);
+    }
+
+    pub fn is_loading(&self) -> bool {
+        self.movie_job.is_running() || self.tv_job.is_running()
+    }
+
+    // Ok(true) on the frame a list arrives
+    pub fn poll(&mut self) -> TmdbResult<bool> {
+        let mut arrived = false;
+        let mut error = None;
+        for (job, genres) in [(&mut self.movie_job, &mut self.movie), (&mut self.tv_job, &mut self.tv)] {
+            match job.poll_owned() {
+                Some(Ok(list)) => {
+                    *genres = list;
+                    arrived = true;
+                }
+                Some(Err(err)) => error = Some(err),
+                None => {}
+            }
+        }
+        match error {
+            Some(err) => Err(err),
+            None => Ok(arrived),
+        }
+    }
+
+    pub fn list(&self, is_tv: bool) -> &[Genre] {
+        if is_tv {
+            &self.tv
+        } else {
+            &self.movie
+        }
+    }
+
+    // Unknown ids are skipped
+    pub fn names(&self, is_tv: bool, ids: &[u32]) -> Vec<String> {
+        let list = self.list(is_tv);
+        ids.iter()
+            .filter_map(|id| list.iter().find(|genre| genre.id == *id))
+            .map(|genre| genre.name.clone())
+            .collect()
+    }
+}
+
+// Results of a paginated listing, pages are appended as they arrive
+struct PagedProductions {
+    listing: Option<Listing>,
+    productions: Option<Rc<[Production]>>,
+    job: Job<TmdbResult<ProductionPage>>,
+    // When the first page was requested
+    loaded_at: Option<Instant>,
+    // Last page that was loaded, 0 if nothing was loaded yet
+    page: u32,
+    total_pages: u32,
+    total_results: u32,
+    sources: Option<PagedSources>,
+}
+
+impl PagedProductions {
+    pub fn new() -> Self {
+        Self {
+            listing: None,
+            productions: None,
+            job: Job::Empty,
+            loaded_at: None,
+            page: 0,
+            total_pages: 0,
+            total_results: 0,
+            sources: None,
+        }
+    }
+
+    // Drops the current results and starts fetching the first page of listing
+    pub fn load(&mut self, listing: Listing, movie_db: &TheMovieDB) {
+        *self = Self::new();
+        self.job = listing.fetch(movie_db, 1, None);
+        self.listing = Some(listing);
+        self.loaded_at = Some(Instant::now());
+    }
+
+    pub fn load_more(&mut self, movie_db: &TheMovieDB) {
+        if let Some(listing) = &self.listing {
+            self.job = listing.fetch(movie_db, self.page + 1, self.sources);
+        }
+    }
+
+    pub fn has_more(&self) -> bool {
+        self.page < self.total_pages
+    }
+
+    // Appends a page once it arrives, returns the error if fetching it failed
+    pub fn poll(&mut self) -> Option<TmdbError> {
+        match self.job.poll_owned()? {
+            Ok(page) => {
+                self.append(page);
+                None
+            }
+            Err(err) => Some(err),
+        }
+    }
+
+    // Result count and the "Load more" button, drawn below the results
+    pub fn footer(&mut self, ui: &mut Ui, movie_db: &TheMovieDB) {
+        let shown = self.productions.as_ref().map_or(0, |productions| productions.len());
+        ui.label(format!("Showing {shown} of {} results", self.total_results));
+        if self.job.is_running() {
+            ui.spinner();
+        } else if self.has_more() && ui.button("Load more").clicked() {
+            self.load_more(movie_db);
+        }
+    }
+
+    pub fn append(&mut self, page: ProductionPage) {
+        let mut productions: Vec<Production> = match &self.productions {
+            Some(existing) => existing.to_vec(),
+            None => Vec::with_capacity(page.productions.len()),
+        };
+        // Pages can overlap when popularity shifts between requests
+        for prod in page.productions {
+            if !productions.iter().any(|existing| existing.is_same(&prod)) {
+                productions.push(prod);
+            }
+        }
+        if self.listing.as_ref().is_some_and(Listing::sorts_by_popularity) {
+            sort_productions_by_popularity(&mut productions);
+        }
+
+        self.productions = Some(productions.into());
+        self.page = page.page;
+        self.total_pages = page.total_pages;
+        self.total_results = page.total_results;
+        self.sources = page.sources;
+    }
+}
+
+fn format_elapsed(elapsed: Duration) -> String {
+    let minutes = elapsed.as_secs() / 60;
+    match minutes {
+        0 => "just now".to_string(),
+        1..=59 => format!("{minutes} min ago"),
+        _ => format!("{} h {} min ago", minutes / 60, minutes % 60),
+    }
+}
+
+fn sort_productions_by_popularity(productions: &mut [Production]) {
+    productions.sort_by(|e1, e2| e2.popularity().total_cmp(&e1.popularity()));
+}
+
+pub fn error_toast(text: impl Into<WidgetText>) -> Toast {
+    Toast {
+        text: text.into(),
+        kind: ToastKind::Error,
+        options: ToastOptions::default()
+            .duration_in_seconds(3.5)
+            .show_progress(true)
+            .show_icon(true),
     }
 }
 
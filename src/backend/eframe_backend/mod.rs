@@ -13,6 +13,7 @@ impl eframe::App for MovieApp {
         if self.config.save_on_exit {
             self.save_data();
         }
+        self.flush_cache();
     }
 }
 
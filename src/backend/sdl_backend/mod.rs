@@ -473,4 +473,5 @@ pub fn run_app() {
     if movie_app.config.save_on_exit {
         movie_app.save_data();
     }
+    movie_app.flush_cache();
 }
@@ -0,0 +1,115 @@
+// A tiny stand-in for the TMDB API that replays recorded JSON responses from disk.
+//
+// Usage: cargo run --bin mock_tmdb -- [address] [fixtures_dir]
+//        defaults to 127.0.0.1:8000 and res/fixtures
+//
+// Then point the app at it in res/config.json:
+//     "api_url": "http://127.0.0.1:8000/3",
+//     "image_url": "http://127.0.0.1:8000/t/p/"
+//
+// A request for /3/movie/550/videos?language=en-US is answered with <fixtures_dir>/movie/550/videos.json.
+// Query parameters are ignored: every search, page, year or language gets the same recorded response, and
+// details are recorded with every append_to_response the app asks for. Every image request is answered
+// with res/no_image.png. Port 0 picks a free port, the first line printed names the actual address.
+
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+const DEFAULT_ADDRESS: &str = "127.0.0.1:8000";
+const DEFAULT_FIXTURES: &str = "res/fixtures";
+const PLACEHOLDER_IMAGE: &[u8] = include_bytes!("../../res/no_image.png");
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let address = args.next().unwrap_or_else(|| DEFAULT_ADDRESS.into());
+    let fixtures: Arc<PathBuf> = Arc::new(args.next().unwrap_or_else(|| DEFAULT_FIXTURES.into()).into());
+
+    let listener = match TcpListener::bind(&address) {
+        Ok(listener) => listener,
+        Err(err) => {
+            eprintln!("ERROR: Failed to bind {address}: {err}");
+            return;
+        }
+    };
+    let address = listener.local_addr().map_or(address, |address| address.to_string());
+    println!("Replaying {} on http://{address}", fixtures.display());
+    serve(listener, fixtures);
+}
+
+// Also started in-process by the client tests, see themoviedb.rs
+pub fn serve(listener: TcpListener, fixtures: Arc<PathBuf>) {
+    for stream in listener.incoming() {
+        let Ok(stream) = stream else {
+            continue;
+        };
+        let fixtures = fixtures.clone();
+        std::thread::spawn(move || {
+            if let Err(err) = handle_connection(stream, &fixtures) {
+                eprintln!("ERROR: {err}");
+            }
+        });
+    }
+}
+
+fn handle_connection(mut stream: TcpStream, fixtures: &Path) -> std::io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+
+    // Drain the headers, the body of a GET is empty
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 || header.trim().is_empty() {
+            break;
+        }
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default();
+    let target = parts.next().unwrap_or_default();
+    let path = target.split('?').next().unwrap_or_default();
+    println!("{method} {target}");
+
+    if method != "GET" {
+        return respond(&mut stream, "405 Method Not Allowed", "application/json", &status_json(405, "Only GET is supported"));
+    }
+
+    if path.starts_with("/t/p/") {
+        return respond(&mut stream, "200 OK", "image/png", PLACEHOLDER_IMAGE);
+    }
+
+    match fixture_path(fixtures, path).map(std::fs::read) {
+        Some(Ok(body)) => respond(&mut stream, "200 OK", "application/json", &body),
+        _ => {
+            let message = format!("No fixture recorded for {path}");
+            respond(&mut stream, "404 Not Found", "application/json", &status_json(34, &message))
+        }
+    }
+}
+
+// Maps /3/tv/1399/season/1 to <fixtures>/tv/1399/season/1.json, refusing anything that escapes the directory
+fn fixture_path(fixtures: &Path, path: &str) -> Option<PathBuf> {
+    let relative = path.strip_prefix("/3").unwrap_or(path).trim_matches('/');
+    if relative.is_empty() || relative.split('/').any(|segment| segment.is_empty() || segment == "..") {
+        return None;
+    }
+    Some(fixtures.join(format!("{relative}.json")))
+}
+
+// Same shape as the errors returned by TMDB
+fn status_json(status_code: u32, message: &str) -> Vec<u8> {
+    let message = message.replace('\\', "\\\\").replace('"', "\\\"");
+    format!("{{\"success\":false,\"status_code\":{status_code},\"status_message\":\"{message}\"}}").into_bytes()
+}
+
+fn respond(stream: &mut TcpStream, status: &str, content_type: &str, body: &[u8]) -> std::io::Result<()> {
+    let head = format!(
+        "HTTP/1.1 {status}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        body.len()
+    );
+    stream.write_all(head.as_bytes())?;
+    stream.write_all(body)?;
+    stream.flush()
+}
@@ -0,0 +1,352 @@
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const INDEX_FILE: &str = "index.json";
+// Writing the whole index on every insert is wasteful while a page of posters and details loads,
+// inserts in between only mark it as unsaved and flush() writes whatever is left on exit
+const INDEX_SAVE_INTERVAL: u64 = 30;
+
+const MINUTE: u64 = 60;
+const HOUR: u64 = 60 * MINUTE;
+const DAY: u64 = 24 * HOUR;
+
+// Curated lists under /movie and /tv that change throughout the day
+const FEED_ENDPOINTS: [&str; 6] = ["/popular", "/top_rated", "/now_playing", "/upcoming", "/airing_today", "/on_the_air"];
+
+// How long a response stays fresh, based on the endpoint it came from.
+// Search results shift quickly, external ids and keywords practically never change.
+pub fn time_to_live(url: &str) -> Duration {
+    let path = url.split('?').next().unwrap_or(url);
+    let secs = if path.contains("/search/") || path.contains("/discover/") {
+        HOUR
+    } else if path.contains("/trending/") || FEED_ENDPOINTS.iter().any(|feed| path.ends_with(feed)) {
+        15 * MINUTE
+    } else if path.contains("/genre/")
+        || path.ends_with("/external_ids")
+        || path.ends_with("/keywords")
+        || path.ends_with("/videos")
+        || path.ends_with("/configuration")
+    {
+        7 * DAY
+    } else if path.contains("/season/") {
+        3 * DAY
+    } else {
+        DAY
+    };
+    Duration::from_secs(secs)
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct CacheEntry {
+    file: String,
+    size: u64,
+    // Unix timestamps in seconds
+    stored_at: u64,
+    last_access: u64,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CacheIndex {
+    entries: HashMap<String, CacheEntry>,
+    total_size: u64,
+    // Unix timestamp of the last write to disk, 0 if it wasn't written since loading
+    #[serde(skip)]
+    saved_at: u64,
+}
+
+// Disk backed cache of raw TMDB responses keyed by request url (endpoint + parameters).
+// Least recently used entries are evicted once the cache grows beyond max_size.
+// Expired entries are kept on disk until evicted so they can still be served while offline.
+pub struct ResponseCache {
+    dir: PathBuf,
+    max_size: AtomicU64,
+    index: Mutex<CacheIndex>,
+}
+
+pub struct CacheStats {
+    pub entries: usize,
+    pub size: u64,
+}
+
+#[allow(dead_code)]
+impl ResponseCache {
+    pub fn new(dir: impl Into<PathBuf>, max_size: u64) -> Self {
+        let dir = dir.into();
+        let index = match fs::read_to_string(dir.join(INDEX_FILE)) {
+            Ok(contents) => serde_json::from_str(&contents).unwrap_or_else(|err| {
+                eprintln!("ERROR: Cache index is corrupted, starting with an empty cache: {err}");
+                CacheIndex::default()
+            }),
+            Err(_) => CacheIndex::default(),
+        };
+
+        Self {
+            dir,
+            max_size: AtomicU64::new(max_size),
+            index: Mutex::new(index),
+        }
+    }
+
+    // Returns the body only if it's still fresh
+    pub fn get(&self, url: &str) -> Option<String> {
+        self.read(url, Some(time_to_live(url)))
+    }
+
+    // Returns the body no matter how old it is, used when TMDB can't be reached
+    pub fn get_stale(&self, url: &str) -> Option<String> {
+        self.read(url, None)
+    }
+
+    fn read(&self, url: &str, ttl: Option<Duration>) -> Option<String> {
+        let mut index = self.index.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let now = unix_now();
+        let entry = index.entries.get_mut(url)?;
+        if let Some(ttl) = ttl {
+            if now.saturating_sub(entry.stored_at) > ttl.as_secs() {
+                return None;
+            }
+        }
+
+        match fs::read_to_string(self.dir.join(&entry.file)) {
+            Ok(body) => {
+                entry.last_access = now;
+                Some(body)
+            }
+            Err(_) => {
+                // The file was removed behind our back
+                let size = entry.size;
+                index.entries.remove(url);
+                index.total_size = index.total_size.saturating_sub(size);
+                None
+            }
+        }
+    }
+
+    pub fn put(&self, url: &str, body: &str) {
+        let size = body.len() as u64;
+        if size > self.max_size.load(Ordering::Relaxed) {
+            return;
+        }
+
+        let mut index = self.index.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        if let Err(err) = fs::create_dir_all(&self.dir) {
+            eprintln!("ERROR: Failed to create the cache directory: {err}");
+            return;
+        }
+
+        let file = file_name(url);
+        if let Err(err) = fs::write(self.dir.join(&file), body) {
+            eprintln!("ERROR: Failed to write a cache entry: {err}");
+            return;
+        }
+
+        let now = unix_now();
+        let entry = CacheEntry {
+            file,
+            size,
+            stored_at: now,
+            last_access: now,
+        };
+        if let Some(previous) = index.entries.insert(url.to_string(), entry) {
+            index.total_size = index.total_size.saturating_sub(previous.size);
+        }
+        index.total_size += size;
+
+        self.evict(&mut index);
+        if now.saturating_sub(index.saved_at) >= INDEX_SAVE_INTERVAL {
+            self.save_index(&mut index);
+        }
+    }
+
+    // Shrinking the limit evicts right away
+    pub fn set_max_size(&self, max_size: u64) {
+        self.max_size.store(max_size, Ordering::Relaxed);
+        let mut index = self.index.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let before = index.entries.len();
+        self.evict(&mut index);
+        if index.entries.len() != before {
+            self.save_index(&mut index);
+        }
+    }
+
+    fn evict(&self, index: &mut CacheIndex) {
+        while index.total_size > self.max_size.load(Ordering::Relaxed) {
+            let oldest = index
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_access)
+                .map(|(url, _)| url.clone());
+            let Some(url) = oldest else {
+                break;
+            };
+            let entry = index.entries.remove(&url).expect("Entry was just found");
+            let _ = fs::remove_file(self.dir.join(&entry.file));
+            index.total_size = index.total_size.saturating_sub(entry.size);
+        }
+    }
+
+    fn save_index(&self, index: &mut CacheIndex) {
+        match serde_json::to_string(index) {
+            Ok(json) => match fs::write(self.dir.join(INDEX_FILE), json) {
+                Ok(_) => index.saved_at = unix_now(),
+                Err(err) => eprintln!("ERROR: Failed to save the cache index: {err}"),
+            },
+            Err(err) => eprintln!("ERROR: Failed to serialize the cache index: {err}"),
+        }
+    }
+
+    // Persists access times and unsaved inserts so eviction order survives a restart
+    pub fn flush(&self) {
+        let mut index = self.index.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        if index.entries.is_empty() {
+            return;
+        }
+        self.save_index(&mut index);
+    }
+
+    pub fn clear(&self) -> Result<(), String> {
+        let mut index = self.index.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        *index = CacheIndex::default();
+        match fs::remove_dir_all(&self.dir) {
+            Ok(_) => Ok(()),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(err) => Err(err.to_string()),
+        }
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        let index = self.index.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        CacheStats {
+            entries: index.entries.len(),
+            size: index.total_size,
+        }
+    }
+}
+
+fn file_name(url: &str) -> String {
+    let mut hasher = DefaultHasher::new();
+    url.hash(&mut hasher);
+    format!("{:016x}.json", hasher.finish())
+}
+
+pub fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|since| since.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SEARCH_URL: &str = "https://api.themoviedb.org/3/search/movie?query=dune";
+    const MOVIE_URL: &str = "https://api.themoviedb.org/3/movie/438631";
+
+    // A fresh directory per test, tests run in parallel
+    fn temp_cache(name: &str, max_size: u64) -> ResponseCache {
+        let dir = std::env::temp_dir().join(format!("movie-gui-cache-{}-{name}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        ResponseCache::new(dir, max_size)
+    }
+
+    // Pretends the entry was stored and last read secs_ago seconds ago
+    fn age(cache: &ResponseCache, url: &str, secs_ago: u64) {
+        let mut index = cache.index.lock().unwrap();
+        let entry = index.entries.get_mut(url).unwrap();
+        entry.stored_at = unix_now() - secs_ago;
+        entry.last_access = entry.stored_at;
+    }
+
+    fn indexed_on_disk(cache: &ResponseCache) -> usize {
+        let contents = fs::read_to_string(cache.dir.join(INDEX_FILE)).unwrap();
+        serde_json::from_str::<CacheIndex>(&contents).unwrap().entries.len()
+    }
+
+    #[test]
+    fn entries_expire_per_endpoint() {
+        let cache = temp_cache("ttl", 1024);
+        cache.put(SEARCH_URL, "search");
+        cache.put(MOVIE_URL, "movie");
+        age(&cache, SEARCH_URL, 2 * HOUR);
+        age(&cache, MOVIE_URL, 2 * HOUR);
+
+        // Search results last an hour, details a day
+        assert_eq!(cache.get(SEARCH_URL), None);
+        assert_eq!(cache.get(MOVIE_URL).as_deref(), Some("movie"));
+
+        age(&cache, MOVIE_URL, 2 * DAY);
+        assert_eq!(cache.get(MOVIE_URL), None);
+        let _ = cache.clear();
+    }
+
+    #[test]
+    fn stale_entries_are_still_served_on_request() {
+        let cache = temp_cache("stale", 1024);
+        cache.put(SEARCH_URL, "search");
+        age(&cache, SEARCH_URL, 30 * DAY);
+
+        assert_eq!(cache.get(SEARCH_URL), None);
+        assert_eq!(cache.get_stale(SEARCH_URL).as_deref(), Some("search"));
+        assert_eq!(cache.get_stale(MOVIE_URL), None);
+        let _ = cache.clear();
+    }
+
+    #[test]
+    fn least_recently_used_entry_is_evicted() {
+        let cache = temp_cache("lru", 10);
+        cache.put(SEARCH_URL, "1234");
+        cache.put(MOVIE_URL, "5678");
+        age(&cache, SEARCH_URL, 2 * MINUTE);
+        age(&cache, MOVIE_URL, MINUTE);
+        // Reading the older entry makes the other one the least recently used
+        assert!(cache.get(SEARCH_URL).is_some());
+
+        cache.put("https://api.themoviedb.org/3/tv/1399", "9012");
+        assert!(cache.get_stale(SEARCH_URL).is_some());
+        assert_eq!(cache.get_stale(MOVIE_URL), None);
+        assert!(!cache.dir.join(file_name(MOVIE_URL)).exists());
+        assert_eq!(cache.stats().size, 8);
+
+        // Bodies larger than the whole cache aren't stored at all
+        cache.put(MOVIE_URL, "too large to fit");
+        assert_eq!(cache.stats().entries, 2);
+        let _ = cache.clear();
+    }
+
+    #[test]
+    fn clear_removes_everything() {
+        let cache = temp_cache("clear", 1024);
+        cache.put(SEARCH_URL, "search");
+        cache.put(MOVIE_URL, "movie");
+
+        assert_eq!(cache.clear(), Ok(()));
+        assert!(!cache.dir.exists());
+        assert_eq!(cache.stats().entries, 0);
+        assert_eq!(cache.stats().size, 0);
+        assert_eq!(cache.get_stale(MOVIE_URL), None);
+        // Clearing twice is fine
+        assert_eq!(cache.clear(), Ok(()));
+    }
+
+    #[test]
+    fn index_writes_are_batched_until_flush() {
+        let cache = temp_cache("index", 1024);
+        cache.put(SEARCH_URL, "search");
+        cache.put(MOVIE_URL, "movie");
+        assert_eq!(indexed_on_disk(&cache), 1);
+
+        cache.flush();
+        assert_eq!(indexed_on_disk(&cache), 2);
+        let reopened = ResponseCache::new(cache.dir.clone(), 1024);
+        assert_eq!(reopened.get(MOVIE_URL).as_deref(), Some("movie"));
+        let _ = cache.clear();
+    }
+}
@@ -1,3 +1,4 @@
+use crate::themoviedb::{DEFAULT_API_URL, DEFAULT_IMAGE_URL};
 use serde::Deserialize;
 use serde::Serialize;
 use std::fs;
@@ -8,10 +9,22 @@ pub struct Config {
     pub access_token: String,
     pub include_adult: bool,
     pub enable_cache: bool,
+    pub max_cache_size_mb: u64,
     pub load_on_startup: bool,
     pub save_on_exit: bool,
     pub autosave: bool,
     pub browser_name: String,
+    // ISO 3166-1 country used for watch providers
+    pub region: String,
+    // Language of titles, overviews and episode names, ISO 639-1 optionally followed by a country ("pl-PL")
+    pub language: String,
+    // Used for text that isn't translated to language
+    pub fallback_language: String,
+    // Provider ids of the streaming services the user pays for
+    pub subscribed_providers: Vec<u32>,
+    // Roots of the TMDB REST API and the image CDN
+    pub api_url: String,
+    pub image_url: String,
 }
 
 impl Default for Config {
@@ -20,10 +33,17 @@ impl Default for Config {
             access_token: "<Replace this with your TMDB access token>".to_string(),
             include_adult: false,
             enable_cache: false,
+            max_cache_size_mb: 64,
             load_on_startup: true,
             save_on_exit: true,
             autosave: false,
             browser_name: "firefox".to_string(),
+            region: "US".to_string(),
+            language: "en-US".to_string(),
+            fallback_language: "en-US".to_string(),
+            subscribed_providers: Vec::new(),
+            api_url: DEFAULT_API_URL.to_string(),
+            image_url: DEFAULT_IMAGE_URL.to_string(),
         }
     }
 }
@@ -64,6 +84,45 @@ impl Config {
                 dots += 1
             }
         }
-        return dots == 2
+        dots == 2
+    }
+}
+
+/// ISO 639-1 language, optionally with an ISO 3166-1 region, like `en` or `en-US`
+pub fn is_language_tag(tag: &str) -> bool {
+    let (language, region) = match tag.split_once('-') {
+        Some((language, region)) => (language, Some(region)),
+        None => (tag, None),
+    };
+    language.len() == 2
+        && language.bytes().all(|byte| byte.is_ascii_lowercase())
+        && region.is_none_or(is_region_code)
+}
+
+/// ISO 3166-1 alpha-2 country code, like `US`
+pub fn is_region_code(code: &str) -> bool {
+    code.len() == 2 && code.bytes().all(|byte| byte.is_ascii_uppercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn language_tags() {
+        for tag in ["en", "en-US", "pt-BR"] {
+            assert!(is_language_tag(tag), "{tag}");
+        }
+        for tag in ["", "e", "EN", "en-", "en-us", "eng-US", "en-USA", "en_US", "en-US-x"] {
+            assert!(!is_language_tag(tag), "{tag}");
+        }
+    }
+
+    #[test]
+    fn region_codes() {
+        assert!(is_region_code("US"));
+        for code in ["", "U", "us", "USA", "U1"] {
+            assert!(!is_region_code(code), "{code}");
+        }
     }
 }
@@ -1,28 +1,57 @@
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Serialize, Deserialize)]
+// Crew jobs worth showing next to the cast, in display order
+const KEY_JOBS: [&str; 6] = [
+    "Director",
+    "Screenplay",
+    "Writer",
+    "Original Music Composer",
+    "Director of Photography",
+    "Creator",
+];
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Credits {
-    id: u32,
-    //simplify it to cast at some point
-    cast: Vec<Actor>,
-    //don't deserialize crew
+    pub id: u32,
+    pub cast: Vec<Actor>,
+    pub crew: Vec<CrewMember>,
 }
 
-#[allow(dead_code)]
 impl Credits {
-    pub fn parse(json: &str) -> Credits {
-        serde_json::from_str(json).expect("Failed to deserialize a Credits object")
+    // Director, writers, composer and DoP grouped by job, people credited twice for the same job are listed once
+    pub fn key_crew(&self) -> Vec<(&'static str, Vec<&CrewMember>)> {
+        let mut key_crew = Vec::new();
+        for job in KEY_JOBS {
+            let mut members: Vec<&CrewMember> = Vec::new();
+            for member in self.crew.iter().filter(|member| member.job == job) {
+                if !members.iter().any(|added| added.id == member.id) {
+                    members.push(member);
+                }
+            }
+            if !members.is_empty() {
+                key_crew.push((job, members));
+            }
+        }
+        key_crew
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Actor {
-    character: String,
-    name: String,
-    original_name: String,
-    credit_id: u32,
-    gender: u32,
-    adult: bool,
+    pub id: u32,
+    pub character: String,
+    pub name: String,
+    pub original_name: String,
+    pub credit_id: String,
+    pub gender: u32,
+    pub adult: bool,
+    pub profile_path: Option<String>,
+    // Billing order, lower comes first
+    pub order: u32,
+    // Only known for series, 0 for movies
+    pub episode_count: u32,
 }
 
 #[allow(dead_code)]
@@ -37,3 +66,106 @@ impl Actor {
         }
     }
 }
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CrewMember {
+    pub id: u32,
+    pub name: String,
+    pub job: String,
+    pub department: String,
+    pub credit_id: String,
+    pub profile_path: Option<String>,
+}
+
+// /tv/{id}/aggregate_credits lists every role and job of a person across all seasons
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct AggregateCredits {
+    id: u32,
+    cast: Vec<AggregateActor>,
+    crew: Vec<AggregateCrewMember>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct AggregateActor {
+    id: u32,
+    name: String,
+    original_name: String,
+    gender: u32,
+    adult: bool,
+    profile_path: Option<String>,
+    order: u32,
+    total_episode_count: u32,
+    roles: Vec<Role>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct Role {
+    credit_id: String,
+    character: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct AggregateCrewMember {
+    id: u32,
+    name: String,
+    department: String,
+    profile_path: Option<String>,
+    jobs: Vec<CrewJob>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct CrewJob {
+    credit_id: String,
+    job: String,
+}
+
+impl From<AggregateCredits> for Credits {
+    fn from(aggregate: AggregateCredits) -> Self {
+        let cast = aggregate
+            .cast
+            .into_iter()
+            .map(|actor| {
+                let characters: Vec<&str> = actor.roles.iter().map(|role| role.character.as_str()).collect();
+                Actor {
+                    id: actor.id,
+                    character: characters.join(" / "),
+                    credit_id: actor.roles.first().map(|role| role.credit_id.clone()).unwrap_or_default(),
+                    name: actor.name,
+                    original_name: actor.original_name,
+                    gender: actor.gender,
+                    adult: actor.adult,
+                    profile_path: actor.profile_path,
+                    order: actor.order,
+                    episode_count: actor.total_episode_count,
+                }
+            })
+            .collect();
+
+        // One entry per job, the same as movie credits
+        let mut crew = Vec::new();
+        for member in aggregate.crew {
+            for job in member.jobs {
+                crew.push(CrewMember {
+                    id: member.id,
+                    name: member.name.clone(),
+                    job: job.job,
+                    department: member.department.clone(),
+                    credit_id: job.credit_id,
+                    profile_path: member.profile_path.clone(),
+                });
+            }
+        }
+
+        Self {
+            id: aggregate.id,
+            cast,
+            crew,
+        }
+    }
+}
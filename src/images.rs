@@ -0,0 +1,132 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ImageKind {
+    Poster,
+    Backdrop,
+    Logo,
+    // People
+    Profile,
+    // Episode screenshots
+    Still,
+}
+
+// The "images" part of /configuration, every kind of image comes in its own set of sizes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ImageConfiguration {
+    pub backdrop_sizes: Vec<String>,
+    pub logo_sizes: Vec<String>,
+    pub poster_sizes: Vec<String>,
+    pub profile_sizes: Vec<String>,
+    pub still_sizes: Vec<String>,
+}
+
+// The sizes TMDB documents, used until /configuration arrives or when it can't be fetched
+impl Default for ImageConfiguration {
+    fn default() -> Self {
+        let sizes = |sizes: &[&str]| sizes.iter().map(|size| size.to_string()).collect();
+        Self {
+            backdrop_sizes: sizes(&["w300", "w780", "w1280", "original"]),
+            logo_sizes: sizes(&["w45", "w92", "w154", "w185", "w300", "w500", "original"]),
+            poster_sizes: sizes(&["w92", "w154", "w185", "w342", "w500", "w780", "original"]),
+            profile_sizes: sizes(&["w45", "w185", "h632", "original"]),
+            still_sizes: sizes(&["w92", "w185", "w300", "original"]),
+        }
+    }
+}
+
+impl ImageConfiguration {
+    fn sizes(&self, kind: ImageKind) -> &[String] {
+        match kind {
+            ImageKind::Poster => &self.poster_sizes,
+            ImageKind::Backdrop => &self.backdrop_sizes,
+            ImageKind::Logo => &self.logo_sizes,
+            ImageKind::Profile => &self.profile_sizes,
+            ImageKind::Still => &self.still_sizes,
+        }
+    }
+
+    // Width over height, None for logos which come in any shape
+    fn aspect_ratio(kind: ImageKind) -> Option<f32> {
+        match kind {
+            ImageKind::Poster | ImageKind::Profile => Some(2.0 / 3.0),
+            ImageKind::Backdrop | ImageKind::Still => Some(16.0 / 9.0),
+            ImageKind::Logo => None,
+        }
+    }
+
+    // How wide an image of this size is, height based sizes (profiles come in h632) go by the aspect ratio
+    fn size_width(kind: ImageKind, size: &str) -> Option<f32> {
+        if let Some(width) = size.strip_prefix('w') {
+            return width.parse().ok();
+        }
+        let height: f32 = size.strip_prefix('h')?.parse().ok()?;
+        Some(height * Self::aspect_ratio(kind)?)
+    }
+
+    // The narrowest size that is at least width pixels wide, "original" when none is
+    pub fn best_size(&self, kind: ImageKind, width: f32) -> &str {
+        self.sizes(kind)
+            .iter()
+            .filter_map(|size| Some((size.as_str(), Self::size_width(kind, size)?)))
+            .filter(|(_, size_width)| *size_width >= width)
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map_or("original", |(size, _)| size)
+    }
+}
+
+// /configuration
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct Configuration {
+    pub images: ImageConfiguration,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn best_size_is_the_narrowest_wide_enough() {
+        let config = ImageConfiguration::default();
+        let cases = [
+            (ImageKind::Poster, 50.0, "w92"),
+            (ImageKind::Poster, 92.0, "w92"),
+            (ImageKind::Poster, 200.0, "w342"),
+            (ImageKind::Poster, 780.0, "w780"),
+            (ImageKind::Poster, 781.0, "original"),
+            (ImageKind::Backdrop, 0.0, "w300"),
+            (ImageKind::Backdrop, 800.0, "w1280"),
+            (ImageKind::Backdrop, 1920.0, "original"),
+            (ImageKind::Logo, 45.0, "w45"),
+            (ImageKind::Logo, 250.0, "w300"),
+            (ImageKind::Logo, 600.0, "original"),
+            (ImageKind::Still, 100.0, "w185"),
+            (ImageKind::Still, 300.0, "w300"),
+            (ImageKind::Still, 400.0, "original"),
+        ];
+        for (kind, width, size) in cases {
+            assert_eq!(config.best_size(kind, width), size, "{kind:?} at {width}");
+        }
+    }
+
+    #[test]
+    fn profiles_use_their_height_based_size() {
+        let config = ImageConfiguration::default();
+        assert_eq!(config.best_size(ImageKind::Profile, 40.0), "w45");
+        assert_eq!(config.best_size(ImageKind::Profile, 185.0), "w185");
+        // h632 is about 421 pixels wide
+        assert_eq!(config.best_size(ImageKind::Profile, 186.0), "h632");
+        assert_eq!(config.best_size(ImageKind::Profile, 421.0), "h632");
+        assert_eq!(config.best_size(ImageKind::Profile, 422.0), "original");
+    }
+
+    #[test]
+    fn unknown_sizes_fall_back_to_original() {
+        let config = ImageConfiguration { logo_sizes: vec!["h100".into(), "original".into()], ..Default::default() };
+        assert_eq!(config.best_size(ImageKind::Logo, 10.0), "original");
+        let config = ImageConfiguration { poster_sizes: Vec::new(), ..Default::default() };
+        assert_eq!(config.best_size(ImageKind::Poster, 10.0), "original");
+    }
+}
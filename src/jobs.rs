@@ -1,57 +1,75 @@
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::fmt::{Debug, Formatter};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{self, AtomicBool, AtomicU64};
+use std::sync::{Arc, Condvar, Mutex, OnceLock};
 use std::thread;
-use std::thread::JoinHandle;
+
+// Requests are rate limited anyway, more workers would only wait on the limiter
+const WORKERS: usize = 6;
 
 #[derive(Debug, Default)]
 pub enum Job<T> {
     #[default]
     Empty,
     Finished(T),
-    InProgress(JoinHandle<T>),
+    InProgress(JobHandle<T>),
+    // The handle is kept so a retry from the tasks window gets back to whoever holds the job
+    Failed(T, JobHandle<T>),
 }
 
 #[allow(dead_code)]
-impl<T> Job<T> {
-    pub fn new<F>(f: F) -> Self
+impl<T: JobOutput> Job<T> {
+    pub fn new<F>(label: impl Into<String>, f: F) -> Self
     where
-        F: FnOnce() -> T,
-        F: Send + 'static,
-        T: Send + 'static,
+        F: Fn() -> T,
+        F: Send + Sync + 'static,
+        T: JobOutput,
     {
-        Self::InProgress(thread::spawn(f))
+        Self::with_priority(Priority::Normal, label, f)
     }
 
-    pub fn poll_owned(&mut self) -> Option<T> {
-        let current_job = std::mem::take(self);
-        match current_job {
-            Self::Empty => None,
-            Self::Finished(data) => Some(data),
-            Self::InProgress(handle) => {
-                if handle.is_finished() {
-                    let value = handle.join().unwrap();
-                    *self = Self::Finished(value);
-                } else {
-                    *self = Self::InProgress(handle)
-                }
+    pub fn with_priority<F>(priority: Priority, label: impl Into<String>, f: F) -> Self
+    where
+        F: Fn() -> T,
+        F: Send + Sync + 'static,
+        T: JobOutput,
+    {
+        Self::InProgress(scheduler().submit(priority, label.into(), Box::new(f)))
+    }
 
+    pub fn is_running(&self) -> bool {
+        matches!(self, Self::InProgress(_))
+    }
+
+    // Queued work is dropped, running work finishes but nobody gets the result
+    pub fn cancel(&mut self) {
+        *self = Self::Empty;
+    }
+
+    // Taking a failed result gives up on it, a retry has nobody to report to afterwards
+    pub fn poll_owned(&mut self) -> Option<T> {
+        if self.settle() {
+            return None;
+        }
+        match std::mem::take(self) {
+            Self::Finished(data) | Self::Failed(data, _) => Some(data),
+            job => {
+                *self = job;
                 None
             }
         }
     }
 
     pub fn poll(&mut self) -> Option<&T> {
+        if self.settle() {
+            return None;
+        }
         match self {
-            Self::Empty => None,
-            Self::Finished(value) => Some(value),
-            Self::InProgress(handle) if !handle.is_finished() => None,
-            Self::InProgress(_) => {
-                let current_job = std::mem::take(self);
-                let Self::InProgress(handle) = current_job else {
-                    unreachable!();
-                };
-                let value = handle.join().unwrap();
-                *self = Self::Finished(value);
-                None
-            }
+            Self::Finished(value) | Self::Failed(value, _) => Some(value),
+            _ => None,
         }
     }
 
@@ -59,15 +77,625 @@ impl<T> Job<T> {
         let current_job = std::mem::take(self);
         match current_job {
             Self::Empty => None,
-            Self::Finished(data) => Some(data),
-            Self::InProgress(handle) => {
-                *self = Self::Empty;
-                Some(handle.join().unwrap())
+            Self::Finished(data) | Self::Failed(data, _) => Some(data),
+            Self::InProgress(handle) => Some(handle.wait()),
+        }
+    }
+
+    // Picks up a result that just arrived, the first one or one of a retry. True on the frame it does.
+    fn settle(&mut self) -> bool {
+        let (Self::InProgress(handle) | Self::Failed(_, handle)) = self else {
+            return false;
+        };
+        let Some(value) = handle.take() else {
+            return false;
+        };
+        let (Self::InProgress(handle) | Self::Failed(_, handle)) = std::mem::take(self) else {
+            unreachable!();
+        };
+        *self = if value.failure().is_some() {
+            Self::Failed(value, handle)
+        } else {
+            Self::Finished(value)
+        };
+        true
+    }
+}
+
+// Higher goes first, jobs of the same priority run in the order they were started
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Priority {
+    // Library upkeep nobody is looking at
+    Low,
+    Normal,
+    // What the user is waiting on, like search results
+    High,
+}
+
+impl Priority {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Priority::Low => "Low",
+            Priority::Normal => "Normal",
+            Priority::High => "High",
+        }
+    }
+}
+
+// A job's result has to stand in for a panic of the worker and tell whether the work failed
+pub trait JobOutput: Send + 'static {
+    fn from_panic(message: String) -> Self;
+    fn failure(&self) -> Option<String>;
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TaskStatus {
+    Queued,
+    Running,
+    Done,
+    Failed(String),
+}
+
+// A snapshot for the tasks window
+pub struct TaskInfo {
+    pub id: u64,
+    pub label: String,
+    pub priority: Priority,
+    pub status: TaskStatus,
+    // Done and total steps, only for jobs that report them
+    pub progress: Option<(usize, usize)>,
+    // Whether whoever started the job still holds it, a retry would have nobody to report to otherwise
+    pub can_retry: bool,
+}
+
+// Whether the job running on this thread was cancelled, long jobs should check it between steps
+pub fn is_cancelled() -> bool {
+    CURRENT_TASK.with(|current| {
+        current
+            .borrow()
+            .as_ref()
+            .is_some_and(|state| state.cancelled.load(atomic::Ordering::Relaxed))
+    })
+}
+
+// Reported by the job running on this thread
+pub fn set_progress(done: usize, total: usize) {
+    CURRENT_TASK.with(|current| {
+        if let Some(state) = current.borrow().as_ref() {
+            *state.progress.lock().unwrap() = Some((done, total));
+        }
+    });
+}
+
+// Queued, running and failed jobs. Successful ones are forgotten, failed ones stay until dismissed.
+pub fn tasks() -> Vec<TaskInfo> {
+    scheduler().tasks.lock().unwrap().iter().map(|task| task.state().info()).collect()
+}
+
+// Cancels a queued or running job, dismisses a failed one
+pub fn cancel_task(id: u64) {
+    scheduler().cancel(id);
+}
+
+// Runs a failed job again, the result replaces the failure in the job that started it
+pub fn retry_task(id: u64) {
+    scheduler().retry(id);
+}
+
+thread_local! {
+    static CURRENT_TASK: RefCell<Option<Arc<TaskState>>> = const { RefCell::new(None) };
+}
+
+// Shared by the handle, the scheduler and the worker running the job
+struct TaskState {
+    id: u64,
+    label: String,
+    priority: Priority,
+    status: Mutex<TaskStatus>,
+    progress: Mutex<Option<(usize, usize)>>,
+    cancelled: AtomicBool,
+    // Set once the JobHandle is dropped
+    abandoned: AtomicBool,
+}
+
+impl TaskState {
+    fn info(&self) -> TaskInfo {
+        TaskInfo {
+            id: self.id,
+            label: self.label.clone(),
+            priority: self.priority,
+            status: self.status.lock().unwrap().clone(),
+            progress: *self.progress.lock().unwrap(),
+            can_retry: !self.abandoned.load(atomic::Ordering::Relaxed),
+        }
+    }
+}
+
+struct Task<T> {
+    state: Arc<TaskState>,
+    work: Box<dyn Fn() -> T + Send + Sync>,
+    output: Mutex<Option<T>>,
+    finished: Condvar,
+}
+
+// Tasks with their result type erased so the scheduler can hold any of them
+trait Runnable: Send + Sync {
+    fn state(&self) -> &Arc<TaskState>;
+    fn run(&self);
+    // Drops a result nobody took yet
+    fn clear_output(&self);
+}
+
+impl<T: JobOutput> Runnable for Task<T> {
+    fn state(&self) -> &Arc<TaskState> {
+        &self.state
+    }
+
+    fn clear_output(&self) {
+        *self.output.lock().unwrap() = None;
+    }
+
+    fn run(&self) {
+        *self.state.status.lock().unwrap() = TaskStatus::Running;
+        CURRENT_TASK.with(|current| *current.borrow_mut() = Some(self.state.clone()));
+        let output = panic::catch_unwind(AssertUnwindSafe(|| (self.work)())).unwrap_or_else(|payload| {
+            let message = match payload.downcast_ref::<&str>() {
+                Some(message) => message.to_string(),
+                None => payload.downcast_ref::<String>().cloned().unwrap_or_else(|| "unknown panic".into()),
+            };
+            T::from_panic(message)
+        });
+        CURRENT_TASK.with(|current| *current.borrow_mut() = None);
+
+        // Settled before the result shows up, a handle dropped right after taking it mustn't cancel
+        scheduler().finish(&self.state, output.failure());
+        *self.output.lock().unwrap() = Some(output);
+        self.finished.notify_all();
+    }
+}
+
+pub struct JobHandle<T> {
+    task: Arc<Task<T>>,
+}
+
+impl<T> JobHandle<T> {
+    fn take(&self) -> Option<T> {
+        self.task.output.lock().unwrap().take()
+    }
+
+    fn wait(&self) -> T {
+        let mut output = self.task.output.lock().unwrap();
+        loop {
+            match output.take() {
+                Some(value) => return value,
+                None => output = self.task.finished.wait(output).unwrap(),
             }
         }
     }
 }
 
+// A job nobody waits on anymore is cancelled, this is what drops stale searches
+impl<T> Drop for JobHandle<T> {
+    fn drop(&mut self) {
+        self.task.state.abandoned.store(true, atomic::Ordering::Relaxed);
+        let status = self.task.state.status.lock().unwrap().clone();
+        if status != TaskStatus::Queued && status != TaskStatus::Running {
+            return;
+        }
+        if self.task.output.lock().unwrap().is_none() {
+            scheduler().cancel(self.task.state.id);
+        }
+    }
+}
+
+impl<T> Debug for JobHandle<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("JobHandle")
+            .field("id", &self.task.state.id)
+            .field("label", &self.task.state.label)
+            .finish()
+    }
+}
+
+struct Queued {
+    priority: Priority,
+    sequence: u64,
+    task: Arc<dyn Runnable>,
+}
+
+impl PartialEq for Queued {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Queued {}
+
+impl PartialOrd for Queued {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+// The heap pops the greatest, so earlier jobs have to compare as greater
+impl Ord for Queued {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.priority.cmp(&other.priority).then_with(|| other.sequence.cmp(&self.sequence))
+    }
+}
+
+struct Scheduler {
+    queue: Mutex<BinaryHeap<Queued>>,
+    available: Condvar,
+    tasks: Mutex<Vec<Arc<dyn Runnable>>>,
+    next_id: AtomicU64,
+}
+
+// Workers are started with the first job
+fn scheduler() -> &'static Scheduler {
+    static SCHEDULER: OnceLock<Scheduler> = OnceLock::new();
+    SCHEDULER.get_or_init(|| {
+        for i in 0..WORKERS {
+            thread::Builder::new()
+                .name(format!("job-worker-{i}"))
+                .spawn(work)
+                .expect("Failed to start a job worker");
+        }
+        Scheduler {
+            queue: Mutex::new(BinaryHeap::new()),
+            available: Condvar::new(),
+            tasks: Mutex::new(Vec::new()),
+            next_id: AtomicU64::new(0),
+        }
+    })
+}
+
+fn work() {
+    let scheduler = scheduler();
+    loop {
+        let task = scheduler.next();
+        if task.state().cancelled.load(atomic::Ordering::Relaxed) {
+            scheduler.forget(task.state().id);
+            continue;
+        }
+        task.run();
+    }
+}
+
+impl Scheduler {
+    fn submit<T: JobOutput>(&self, priority: Priority, label: String, work: Box<dyn Fn() -> T + Send + Sync>) -> JobHandle<T> {
+        let state = Arc::new(TaskState {
+            id: self.next_id.fetch_add(1, atomic::Ordering::Relaxed),
+            label,
+            priority,
+            status: Mutex::new(TaskStatus::Queued),
+            progress: Mutex::new(None),
+            cancelled: AtomicBool::new(false),
+            abandoned: AtomicBool::new(false),
+        });
+        let task = Arc::new(Task {
+            state,
+            work,
+            output: Mutex::new(None),
+            finished: Condvar::new(),
+        });
+        self.tasks.lock().unwrap().push(task.clone());
+        self.enqueue(task.clone());
+        JobHandle { task }
+    }
+
+    fn enqueue(&self, task: Arc<dyn Runnable>) {
+        let queued = Queued {
+            priority: task.state().priority,
+            sequence: self.next_id.fetch_add(1, atomic::Ordering::Relaxed),
+            task,
+        };
+        self.queue.lock().unwrap().push(queued);
+        self.available.notify_one();
+    }
+
+    fn next(&self) -> Arc<dyn Runnable> {
+        let mut queue = self.queue.lock().unwrap();
+        loop {
+            match queue.pop() {
+                Some(queued) => return queued.task,
+                None => queue = self.available.wait(queue).unwrap(),
+            }
+        }
+    }
+
+    // Successful and cancelled jobs are forgotten, failures stay listed until retried or dismissed
+    fn finish(&self, state: &TaskState, failure: Option<String>) {
+        match failure {
+            Some(message) => {
+                if state.cancelled.load(atomic::Ordering::Relaxed) {
+                    self.forget(state.id);
+                }
+                *state.status.lock().unwrap() = TaskStatus::Failed(message);
+            }
+            None => {
+                self.forget(state.id);
+                *state.status.lock().unwrap() = TaskStatus::Done;
+            }
+        }
+    }
+
+    fn forget(&self, id: u64) {
+        self.tasks.lock().unwrap().retain(|task| task.state().id != id);
+    }
+
+    fn find(&self, id: u64) -> Option<Arc<dyn Runnable>> {
+        self.tasks.lock().unwrap().iter().find(|task| task.state().id == id).cloned()
+    }
+
+    // Queued jobs are skipped by the workers, running ones may check is_cancelled(), a failed one is dismissed
+    fn cancel(&self, id: u64) {
+        let Some(task) = self.find(id) else {
+            return;
+        };
+        let state = task.state();
+        state.cancelled.store(true, atomic::Ordering::Relaxed);
+        if matches!(*state.status.lock().unwrap(), TaskStatus::Failed(_)) {
+            self.forget(id);
+        }
+    }
+
+    // The failed result may still be waiting to be polled, the new one takes its place
+    fn retry(&self, id: u64) {
+        let Some(task) = self.find(id) else {
+            return;
+        };
+        let state = task.state();
+        if state.abandoned.load(atomic::Ordering::Relaxed) {
+            return;
+        }
+        {
+            let mut status = state.status.lock().unwrap();
+            if !matches!(*status, TaskStatus::Failed(_)) {
+                return;
+            }
+            *status = TaskStatus::Queued;
+        }
+        *state.progress.lock().unwrap() = None;
+        task.clear_output();
+        self.enqueue(task);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicUsize;
+    use std::time::{Duration, Instant};
+
+    // Every test shares the one pool, they'd fill each other's workers when run in parallel
+    static POOL: Mutex<()> = Mutex::new(());
+
+    type Output = Result<usize, String>;
+
+    impl JobOutput for Output {
+        fn from_panic(message: String) -> Self {
+            Err(message)
+        }
+
+        fn failure(&self) -> Option<String> {
+            self.as_ref().err().cloned()
+        }
+    }
+
+    fn lock_pool() -> std::sync::MutexGuard<'static, ()> {
+        POOL.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn wait_until(mut condition: impl FnMut() -> bool) {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while !condition() {
+            assert!(Instant::now() < deadline, "timed out");
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    // Keeps every worker busy until permits are handed out, one per blocker
+    struct Blockers {
+        permits: Arc<AtomicUsize>,
+        jobs: Vec<Job<Output>>,
+    }
+
+    impl Blockers {
+        fn start() -> Self {
+            let permits = Arc::new(AtomicUsize::new(0));
+            let running = Arc::new(AtomicUsize::new(0));
+            let jobs = (0..WORKERS)
+                .map(|_| {
+                    let permits = permits.clone();
+                    let running = running.clone();
+                    Job::with_priority(Priority::High, "blocker", move || {
+                        running.fetch_add(1, atomic::Ordering::SeqCst);
+                        while permits
+                            .fetch_update(atomic::Ordering::SeqCst, atomic::Ordering::SeqCst, |left| left.checked_sub(1))
+                            .is_err()
+                        {
+                            thread::sleep(Duration::from_millis(1));
+                        }
+                        Ok(0)
+                    })
+                })
+                .collect();
+            wait_until(|| running.load(atomic::Ordering::SeqCst) == WORKERS);
+            Self { permits, jobs }
+        }
+
+        fn release(&self, count: usize) {
+            self.permits.fetch_add(count, atomic::Ordering::SeqCst);
+        }
+
+        fn finish(mut self) {
+            self.release(WORKERS);
+            for job in &mut self.jobs {
+                job.poll_blocking();
+            }
+        }
+    }
+
+    #[test]
+    fn higher_priority_runs_first_then_in_order() {
+        let _pool = lock_pool();
+        let blockers = Blockers::start();
+        let order = Arc::new(Mutex::new(Vec::new()));
+        let mut jobs: Vec<_> = [(Priority::Low, 0), (Priority::Normal, 1), (Priority::High, 2), (Priority::Normal, 3)]
+            .into_iter()
+            .map(|(priority, index)| {
+                let order = order.clone();
+                Job::with_priority(priority, "ordered", move || {
+                    order.lock().unwrap().push(index);
+                    Ok(index)
+                })
+            })
+            .collect();
+
+        // A single free worker takes the queued jobs one after another
+        blockers.release(1);
+        for job in &mut jobs {
+            job.poll_blocking();
+        }
+        assert_eq!(*order.lock().unwrap(), [2, 1, 3, 0]);
+        blockers.finish();
+    }
+
+    #[test]
+    fn cancelled_jobs_never_run() {
+        let _pool = lock_pool();
+        let blockers = Blockers::start();
+        let ran = Arc::new(AtomicBool::new(false));
+        let mut cancelled = {
+            let ran = ran.clone();
+            Job::new("cancelled", move || {
+                ran.store(true, atomic::Ordering::SeqCst);
+                Ok(0)
+            })
+        };
+        let Job::InProgress(handle) = &cancelled else {
+            panic!("the job should be queued");
+        };
+        let id = handle.task.state.id;
+        assert!(tasks().iter().any(|task| task.id == id && task.status == TaskStatus::Queued));
+        cancelled.cancel();
+
+        // Runs after the cancelled one would have
+        let mut later: Job<Output> = Job::with_priority(Priority::Low, "later", || Ok(1));
+        blockers.release(1);
+        assert_eq!(later.poll_blocking(), Some(Ok(1)));
+        assert!(!ran.load(atomic::Ordering::SeqCst));
+        assert!(tasks().iter().all(|task| task.id != id));
+        blockers.finish();
+    }
+
+    #[test]
+    fn panics_become_failures() {
+        let _pool = lock_pool();
+        let handle = scheduler().submit(Priority::Normal, "panicking".into(), Box::new(|| -> Output {
+            panic!("lost the connection")
+        }));
+
+        assert_eq!(handle.wait(), Err("lost the connection".to_string()));
+        assert_eq!(
+            *handle.task.state.status.lock().unwrap(),
+            TaskStatus::Failed("lost the connection".into())
+        );
+        // Listed until dismissed
+        let id = handle.task.state.id;
+        assert!(tasks().iter().any(|task| task.id == id && task.status == TaskStatus::Failed("lost the connection".into())));
+        cancel_task(id);
+        assert!(tasks().iter().all(|task| task.id != id));
+
+        // The worker survives
+        let mut after_panic: Job<Output> = Job::new("after_panic", || Ok(2));
+        assert_eq!(after_panic.poll_blocking(), Some(Ok(2)));
+    }
+
+    #[test]
+    fn successful_jobs_are_done_and_forgotten() {
+        let _pool = lock_pool();
+        let handle = scheduler().submit(Priority::Normal, "succeeding".into(), Box::new(|| -> Output { Ok(3) }));
+
+        assert_eq!(handle.wait(), Ok(3));
+        assert_eq!(*handle.task.state.status.lock().unwrap(), TaskStatus::Done);
+        assert!(tasks().iter().all(|task| task.id != handle.task.state.id));
+    }
+
+    #[test]
+    fn retry_reports_back_to_the_job() {
+        let _pool = lock_pool();
+        let attempts = Arc::new(AtomicUsize::new(0));
+        let mut job: Job<Output> = {
+            let attempts = attempts.clone();
+            Job::new("flaky", move || match attempts.fetch_add(1, atomic::Ordering::SeqCst) {
+                0 => Err("timed out".into()),
+                attempt => Ok(attempt),
+            })
+        };
+        wait_until(|| job.poll().is_some());
+        assert_eq!(job.poll(), Some(&Err("timed out".to_string())));
+
+        let Job::Failed(_, handle) = &job else {
+            panic!("the failure should keep its handle");
+        };
+        let id = handle.task.state.id;
+        let listed = tasks().into_iter().find(|task| task.id == id).expect("failed jobs stay listed");
+        assert!(listed.can_retry);
+
+        retry_task(id);
+        wait_until(|| matches!(job.poll(), Some(Ok(_))));
+        assert_eq!(job.poll_owned(), Some(Ok(1)));
+        assert!(tasks().iter().all(|task| task.id != id));
+    }
+
+    #[test]
+    fn abandoned_failures_cannot_be_retried() {
+        let _pool = lock_pool();
+        let mut job: Job<Output> = Job::new("abandoned", || Err("not found".into()));
+        wait_until(|| job.poll().is_some());
+        let Job::Failed(_, handle) = &job else {
+            panic!("the failure should keep its handle");
+        };
+        let id = handle.task.state.id;
+        job.cancel();
+
+        let listed = tasks().into_iter().find(|task| task.id == id).expect("failed jobs stay listed");
+        assert!(!listed.can_retry);
+        retry_task(id);
+        let status = tasks().into_iter().find(|task| task.id == id).map(|task| task.status);
+        assert!(matches!(status, Some(TaskStatus::Failed(_))));
+        cancel_task(id);
+    }
+
+    #[test]
+    fn no_more_than_the_worker_count_run_at_once() {
+        let _pool = lock_pool();
+        let running = Arc::new(AtomicUsize::new(0));
+        let most = Arc::new(AtomicUsize::new(0));
+        let mut jobs: Vec<Job<Output>> = (0..WORKERS * 3)
+            .map(|_| {
+                let running = running.clone();
+                let most = most.clone();
+                Job::new("bounded", move || {
+                    let now_running = running.fetch_add(1, atomic::Ordering::SeqCst) + 1;
+                    most.fetch_max(now_running, atomic::Ordering::SeqCst);
+                    thread::sleep(Duration::from_millis(20));
+                    running.fetch_sub(1, atomic::Ordering::SeqCst);
+                    Ok(now_running)
+                })
+            })
+            .collect();
+
+        for job in &mut jobs {
+            job.poll_blocking();
+        }
+        let most = most.load(atomic::Ordering::SeqCst);
+        assert!(most > 1 && most <= WORKERS, "{most} jobs ran at once");
+    }
+}
+
 // #[derive(Debug)]
 // pub enum Job<T> {
 //     Done(T),
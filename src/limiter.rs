@@ -1,10 +1,23 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Condvar, Mutex, MutexGuard};
 use std::time::{Duration, Instant};
 
-#[allow(dead_code)]
+// Token bucket shared by every thread that talks to the API. Holds up to max_hits tokens
+// and regains them evenly over time_frame, so bursts are allowed but the average rate is capped.
 pub struct RateLimiter {
     pub max_hits: usize,
     pub time_frame: Duration,
-    hits: Vec<Instant>,
+    bucket: Mutex<Bucket>,
+    refilled: Condvar,
+    // Threads currently blocked in acquire()
+    waiting: AtomicUsize,
+}
+
+struct Bucket {
+    tokens: f64,
+    last_refill: Instant,
+    // Set after a 429, nobody gets a token before this moment
+    paused_until: Option<Instant>,
 }
 
 #[allow(dead_code)]
@@ -13,31 +26,182 @@ impl RateLimiter {
         Self {
             max_hits,
             time_frame,
-            hits: Vec::with_capacity(max_hits),
+            bucket: Mutex::new(Bucket {
+                tokens: max_hits as f64,
+                last_refill: Instant::now(),
+                paused_until: None,
+            }),
+            refilled: Condvar::new(),
+            waiting: AtomicUsize::new(0),
         }
     }
 
-    pub fn hit(&mut self) -> bool {
-        let now = Instant::now();
+    fn lock(&self) -> MutexGuard<'_, Bucket> {
+        self.bucket.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn refill(&self, bucket: &mut Bucket, now: Instant) {
+        let elapsed = now.duration_since(bucket.last_refill).as_secs_f64();
+        let per_second = self.max_hits as f64 / self.time_frame.as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * per_second).min(self.max_hits as f64);
+        bucket.last_refill = now;
+    }
 
-        let mut last_index = 0;
-        for (i, time_stamp) in self.hits.iter().enumerate() {
-            if now.duration_since(*time_stamp) > self.time_frame {
-                last_index = i;
-            } else {
-                break;
+    // How long until a token is available, None if one can be taken right away
+    fn wait_time(&self, bucket: &mut Bucket, now: Instant) -> Option<Duration> {
+        if let Some(until) = bucket.paused_until {
+            if until > now {
+                return Some(until - now);
             }
+            bucket.paused_until = None;
         }
 
-        if last_index > 0 {
-            self.hits.drain(0..=last_index);
+        self.refill(bucket, now);
+        if bucket.tokens >= 1.0 {
+            return None;
         }
+        let per_second = self.max_hits as f64 / self.time_frame.as_secs_f64();
+        Some(Duration::from_secs_f64((1.0 - bucket.tokens) / per_second))
+    }
+
+    // Takes a token if there is one, never blocks
+    pub fn hit(&self) -> bool {
+        self.try_acquire_at(Instant::now())
+    }
 
-        if self.hits.len() > self.max_hits {
+    // hit() as of the given moment, now must not go back in time between calls
+    pub fn try_acquire_at(&self, now: Instant) -> bool {
+        let mut bucket = self.lock();
+        if self.wait_time(&mut bucket, now).is_some() {
             return false;
         }
-
-        self.hits.push(now);
+        bucket.tokens -= 1.0;
         true
     }
+
+    // Blocks the calling thread until a token is available
+    pub fn acquire(&self) {
+        self.waiting.fetch_add(1, Ordering::SeqCst);
+        let mut bucket = self.lock();
+        while let Some(wait) = self.wait_time(&mut bucket, Instant::now()) {
+            bucket = match self.refilled.wait_timeout(bucket, wait) {
+                Ok((guard, _)) => guard,
+                Err(poisoned) => poisoned.into_inner().0,
+            };
+        }
+        bucket.tokens -= 1.0;
+        self.waiting.fetch_sub(1, Ordering::SeqCst);
+    }
+
+    // Holds back every request for the given duration, used when the server answers with 429
+    pub fn pause_for(&self, duration: Duration) {
+        self.pause_at(Instant::now(), duration);
+    }
+
+    pub fn pause_at(&self, now: Instant, duration: Duration) {
+        let mut bucket = self.lock();
+        let until = now + duration;
+        if bucket.paused_until.is_none_or(|current| current < until) {
+            bucket.paused_until = Some(until);
+        }
+        // Tokens come back only after the pause, otherwise a full burst would follow it
+        bucket.tokens = 0.0;
+        bucket.last_refill = bucket.last_refill.max(until);
+        self.refilled.notify_all();
+    }
+
+    pub fn queue_depth(&self) -> usize {
+        self.waiting.load(Ordering::SeqCst)
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.is_paused_at(Instant::now())
+    }
+
+    pub fn is_paused_at(&self, now: Instant) -> bool {
+        self.lock().paused_until.is_some_and(|until| until > now)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::thread;
+
+    // 4 requests per second, a token every 250ms
+    fn limiter() -> (RateLimiter, Instant) {
+        let limiter = RateLimiter::new(4, Duration::from_secs(1));
+        let start = limiter.lock().last_refill;
+        (limiter, start)
+    }
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    #[test]
+    fn allows_a_full_burst() {
+        let (limiter, start) = limiter();
+        for _ in 0..4 {
+            assert!(limiter.try_acquire_at(start));
+        }
+        assert!(!limiter.try_acquire_at(start));
+    }
+
+    #[test]
+    fn refills_evenly_up_to_capacity() {
+        let (limiter, start) = limiter();
+        while limiter.try_acquire_at(start) {}
+
+        assert!(!limiter.try_acquire_at(start + ms(200)));
+        assert!(limiter.try_acquire_at(start + ms(250)));
+        assert!(!limiter.try_acquire_at(start + ms(300)));
+
+        // A long break doesn't allow more than a burst
+        let later = start + Duration::from_secs(60);
+        for _ in 0..4 {
+            assert!(limiter.try_acquire_at(later));
+        }
+        assert!(!limiter.try_acquire_at(later));
+    }
+
+    #[test]
+    fn pause_holds_back_everyone_and_empties_the_bucket() {
+        let (limiter, start) = limiter();
+        limiter.pause_at(start, Duration::from_secs(2));
+
+        assert!(limiter.is_paused_at(start + ms(1999)));
+        assert!(!limiter.try_acquire_at(start + ms(1999)));
+        // A shorter pause doesn't cut the current one short
+        limiter.pause_at(start + ms(1000), ms(500));
+        assert!(limiter.is_paused_at(start + ms(1800)));
+
+        // Tokens only come back at the refill rate once it's over, not as a full burst
+        assert!(!limiter.is_paused_at(start + ms(2000)));
+        assert!(!limiter.try_acquire_at(start + ms(2000)));
+        assert!(limiter.try_acquire_at(start + ms(2250)));
+        assert!(!limiter.try_acquire_at(start + ms(2250)));
+    }
+
+    #[test]
+    fn counts_blocked_threads() {
+        let limiter = Arc::new(RateLimiter::new(1, ms(50)));
+        assert!(limiter.hit());
+        assert_eq!(limiter.queue_depth(), 0);
+
+        let waiter = {
+            let limiter = limiter.clone();
+            thread::spawn(move || limiter.acquire())
+        };
+        // The token comes back after 50ms, long enough to see the thread waiting for it
+        let mut saw_waiting = false;
+        while !saw_waiting && !waiter.is_finished() {
+            saw_waiting = limiter.queue_depth() == 1;
+            thread::yield_now();
+        }
+        waiter.join().unwrap();
+        assert!(saw_waiting);
+        assert_eq!(limiter.queue_depth(), 0);
+    }
 }
@@ -1,18 +1,28 @@
 mod app;
 mod backend;
+mod cache;
 mod config;
 mod credits;
+mod images;
 mod jobs;
 mod limiter;
 mod movies;
+mod person;
 mod production;
+mod refresh;
+mod releases;
+mod search;
 mod series;
 mod themoviedb;
 mod view;
 
-pub const LICENSE: &str = include_str!("../LICENSE.md");
+// The client tests replay the fixtures with the mock server
+#[cfg(test)]
+#[allow(dead_code)]
+#[path = "bin/mock_tmdb.rs"]
+mod mock_tmdb;
 
-// TODO: Add exception handling to all requests that can fail to avoid crashing the app
+pub const LICENSE: &str = include_str!("../LICENSE.md");
 
 fn main() {
     #[cfg(feature = "glfw_backend")]
@@ -1,19 +1,39 @@
+use crate::credits::Credits;
+use crate::production::{Genre, Images, Keyword, ProductionIds, Trailer};
+use crate::releases::CountryRelease;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Movie {
     pub id: u32,
     pub title: String,
+    // Title in the original language, title is localized
+    #[serde(default)]
+    pub original_title: String,
     pub original_language: String,
     pub overview: String,
     pub popularity: f32,
     pub poster_path: Option<String>,
     pub release_date: String,
+    // Resolved to names through the genre catalog
+    #[serde(default)]
+    pub genre_ids: Vec<u32>,
     pub vote_average: f32,
     pub vote_count: u32,
     pub adult: bool,
 }
 
+impl Movie {
+    // Libraries saved before original titles were kept fall back to the localized one
+    pub fn display_title(&self, original: bool) -> &str {
+        if original && !self.original_title.is_empty() {
+            &self.original_title
+        } else {
+            &self.title
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct MovieDetails {
     pub backdrop_path: Option<String>,
@@ -25,9 +45,54 @@ pub struct MovieDetails {
     pub production_companies: Vec<ProductionCompany>,
     pub status: String,
     pub tagline: String,
+    #[serde(default)]
+    pub belongs_to_collection: Option<CollectionRef>,
+
+    #[serde(default)]
+    pub genres: Vec<Genre>,
+}
+
+// Everything the movie view and the context menus need, fetched in a single request
+#[derive(Debug)]
+pub struct MovieFullDetails {
+    pub details: MovieDetails,
+    pub credits: Credits,
+    pub trailers: Vec<Trailer>,
+    pub keywords: Vec<Keyword>,
+    pub external_ids: ProductionIds,
+    pub images: Images,
+    pub releases: Vec<CountryRelease>,
+}
+
+// The franchise a movie is part of, as embedded in the movie details
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CollectionRef {
+    pub id: u32,
+    pub name: String,
+    pub poster_path: Option<String>,
+    pub backdrop_path: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Collection {
+    pub id: u32,
+    pub name: String,
+    pub overview: String,
+    pub poster_path: Option<String>,
+    pub backdrop_path: Option<String>,
+    // Parsed one by one so a single unannounced film doesn't break the whole collection, kept in release order
+    #[serde(skip)]
+    pub parts: Vec<Movie>,
+}
 
-    #[serde(skip_deserializing)]
-    pub genres: Vec<String>,
+impl Collection {
+    // Films without a release date are still to come, they go last
+    pub fn sort_parts(&mut self) {
+        self.parts.sort_by(|a, b| {
+            a.release_date.is_empty().cmp(&b.release_date.is_empty()).then(a.release_date.cmp(&b.release_date))
+        });
+    }
 }
 
 impl MovieDetails {
@@ -76,7 +141,15 @@ pub struct UserMovie {
     #[serde(default)]
     pub watched: bool,
     #[serde(default)]
-    pub favorite: bool
+    pub favorite: bool,
+    // Age rating in the configured region, None until fetched, empty if the movie isn't rated there
+    #[serde(default)]
+    pub certification: Option<String>,
+    // Picked in the artwork browser, replace the ones TMDB picked
+    #[serde(default)]
+    pub custom_poster: Option<String>,
+    #[serde(default)]
+    pub custom_backdrop: Option<String>,
 }
 
 impl UserMovie {
@@ -86,7 +159,14 @@ impl UserMovie {
             note: String::new(),
             user_rating: 0.0,
             watched: false,
-            favorite: false
+            favorite: false,
+            certification: None,
+            custom_poster: None,
+            custom_backdrop: None,
         }
     }
+
+    pub fn poster_path(&self) -> Option<&String> {
+        self.custom_poster.as_ref().or(self.movie.poster_path.as_ref())
+    }
 }
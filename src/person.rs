@@ -0,0 +1,96 @@
+use crate::production::Production;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SearchedPerson {
+    pub id: u32,
+    pub name: String,
+    pub known_for_department: String,
+    pub profile_path: Option<String>,
+    pub popularity: f32,
+    pub adult: bool,
+    // Filled in from the "known_for" array which mixes movies and series
+    #[serde(skip)]
+    pub known_for: Vec<Production>,
+}
+
+impl SearchedPerson {
+    // Comma separated titles of the known works
+    pub fn known_for_titles(&self) -> String {
+        let titles: Vec<&str> = self.known_for.iter().map(|prod| prod.title()).collect();
+        titles.join(", ")
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PersonDetails {
+    pub id: u32,
+    pub name: String,
+    pub biography: String,
+    // YYYY-MM-DD
+    pub birthday: Option<String>,
+    pub deathday: Option<String>,
+    pub place_of_birth: Option<String>,
+    pub profile_path: Option<String>,
+    pub known_for_department: String,
+    pub popularity: f32,
+    pub imdb_id: Option<String>,
+}
+
+impl PersonDetails {
+    // "1963-12-18 - 2020-01-01" or just the birthday for the living
+    pub fn lifespan(&self) -> String {
+        match (&self.birthday, &self.deathday) {
+            (Some(birthday), Some(deathday)) => format!("{birthday} - {deathday}"),
+            (Some(birthday), None) => format!("Born {birthday}"),
+            (None, Some(deathday)) => format!("Died {deathday}"),
+            (None, None) => "Unknown".to_string(),
+        }
+    }
+}
+
+// A movie or series from /person/{id}/combined_credits with everything the person did in it
+#[derive(Debug, Clone)]
+pub struct PersonCredit {
+    pub production: Production,
+    // Characters played and crew jobs, e.g. "Narrator, Producer"
+    pub roles: Vec<String>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum CreditSort {
+    Newest,
+    Oldest,
+    Popularity,
+}
+
+impl CreditSort {
+    pub const ALL: [CreditSort; 3] = [CreditSort::Newest, CreditSort::Oldest, CreditSort::Popularity];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            CreditSort::Newest => "Newest",
+            CreditSort::Oldest => "Oldest",
+            CreditSort::Popularity => "Popularity",
+        }
+    }
+}
+
+pub fn sort_credits(credits: &mut [PersonCredit], sort: CreditSort) {
+    match sort {
+        // Dates are YYYY-MM-DD so they compare as strings, undated (announced) titles go first
+        CreditSort::Newest => credits.sort_by(|a, b| {
+            let a = a.production.release_date();
+            let b = b.production.release_date();
+            b.is_empty().cmp(&a.is_empty()).then(b.cmp(a))
+        }),
+        CreditSort::Oldest => credits.sort_by(|a, b| {
+            let a = a.production.release_date();
+            let b = b.production.release_date();
+            a.is_empty().cmp(&b.is_empty()).then(a.cmp(b))
+        }),
+        CreditSort::Popularity => credits.sort_by(|a, b| b.production.popularity().total_cmp(&a.production.popularity())),
+    }
+}
@@ -1,17 +1,132 @@
 use std::collections::{HashMap, HashSet};
 use crate::movies::{Movie, UserMovie};
+use crate::person::SearchedPerson;
 use crate::series::{SearchedSeries, SeasonNotes, UserSeries};
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use std::fs::File;
 use std::io::{BufReader, Write};
 
+#[derive(Debug, Clone)]
 pub enum Production {
     Movie(Movie),
     SearchedSeries(SearchedSeries),
+    Person(SearchedPerson),
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+impl Production {
+    pub fn popularity(&self) -> f32 {
+        match self {
+            Production::Movie(movie) => movie.popularity,
+            Production::SearchedSeries(series) => series.popularity,
+            Production::Person(person) => person.popularity,
+        }
+    }
+
+    pub fn title(&self) -> &str {
+        match self {
+            Production::Movie(movie) => &movie.title,
+            Production::SearchedSeries(series) => &series.name,
+            Production::Person(person) => &person.name,
+        }
+    }
+
+    // First air date for series, people don't have one
+    pub fn release_date(&self) -> &str {
+        match self {
+            Production::Movie(movie) => &movie.release_date,
+            Production::SearchedSeries(series) => &series.first_air_date,
+            Production::Person(_) => "",
+        }
+    }
+
+    // Profile picture for people
+    pub fn poster_path(&self) -> Option<&String> {
+        match self {
+            Production::Movie(movie) => movie.poster_path.as_ref(),
+            Production::SearchedSeries(series) => series.poster_path.as_ref(),
+            Production::Person(person) => person.profile_path.as_ref(),
+        }
+    }
+
+    pub fn is_same(&self, other: &Production) -> bool {
+        match (self, other) {
+            (Production::Movie(movie), Production::Movie(other)) => movie.id == other.id,
+            (Production::SearchedSeries(series), Production::SearchedSeries(other)) => series.id == other.id,
+            (Production::Person(person), Production::Person(other)) => person.id == other.id,
+            _ => false,
+        }
+    }
+}
+
+// Ids of everything in the user's library, used to mark titles that were already added
+pub struct LibraryIds {
+    movies: HashSet<u32>,
+    series: HashSet<u32>,
+    watched_movies: HashSet<u32>,
+}
+
+impl LibraryIds {
+    pub fn new(user_movies: &[UserMovie], user_series: &[UserSeries]) -> Self {
+        Self {
+            movies: user_movies.iter().map(|user_movie| user_movie.movie.id).collect(),
+            series: user_series.iter().map(|user_series| user_series.series.id).collect(),
+            watched_movies: user_movies
+                .iter()
+                .filter(|user_movie| user_movie.watched)
+                .map(|user_movie| user_movie.movie.id)
+                .collect(),
+        }
+    }
+
+    pub fn is_movie_watched(&self, id: u32) -> bool {
+        self.watched_movies.contains(&id)
+    }
+
+    pub fn contains_entry(&self, is_tv: bool, id: u32) -> bool {
+        if is_tv {
+            self.series.contains(&id)
+        } else {
+            self.movies.contains(&id)
+        }
+    }
+
+    pub fn contains(&self, prod: &Production) -> bool {
+        match prod {
+            Production::Movie(movie) => self.movies.contains(&movie.id),
+            Production::SearchedSeries(series) => self.series.contains(&series.id),
+            Production::Person(_) => false,
+        }
+    }
+}
+
+// One page of a paginated TMDB listing
+pub struct ProductionPage {
+    pub productions: Vec<Production>,
+    pub page: u32,
+    pub total_pages: u32,
+    pub total_results: u32,
+    // Set when the page merges movie and series listings paged side by side, how far each of them goes
+    pub sources: Option<PagedSources>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PageTotals {
+    pub total_pages: u32,
+    pub total_results: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PagedSources {
+    pub movies: PageTotals,
+    pub series: PageTotals,
+}
+
+const IMDB_TITLE: &str = "https://www.imdb.com/title/";
+const IMDB_FIND: &str = "https://www.imdb.com/find/?q=";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
 pub struct ProductionIds {
     pub id: u32,
     pub facebook_id: Option<String>,
@@ -25,7 +140,17 @@ pub struct ProductionIds {
     pub wikidata_id: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+impl ProductionIds {
+    // Falls back to searching IMDB for the title when TMDB doesn't know the IMDB id
+    pub fn imdb_url(&self, title: &str) -> String {
+        match self.imdb_id.as_deref().filter(|imdb_id| !imdb_id.is_empty()) {
+            Some(imdb_id) => format!("{IMDB_TITLE}{imdb_id}"),
+            None => format!("{IMDB_FIND}{title}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Trailer {
     pub name: String,
     pub key: String,
@@ -41,10 +166,85 @@ impl Trailer {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Artwork {
+    pub file_path: String,
+    pub width: u32,
+    pub height: u32,
+    // None for images without any text on them
+    pub iso_639_1: Option<String>,
+    pub vote_average: f32,
+    pub vote_count: u32,
+}
+
+// Every poster, backdrop and logo TMDB has for a production
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Images {
+    pub posters: Vec<Artwork>,
+    pub backdrops: Vec<Artwork>,
+    pub logos: Vec<Artwork>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Genre {
+    pub id: u32,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WatchProvider {
+    pub provider_id: u32,
+    pub provider_name: String,
+    pub logo_path: Option<String>,
+    // Lower is more prominent
+    pub display_priority: u32,
+}
+
+// Where a production can be watched in a single region, data comes from JustWatch through TMDB
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WatchProviders {
+    // TMDB page listing the offers, JustWatch requires linking back to it
+    pub link: Option<String>,
+    pub flatrate: Vec<WatchProvider>,
+    pub free: Vec<WatchProvider>,
+    pub ads: Vec<WatchProvider>,
+    pub rent: Vec<WatchProvider>,
+    pub buy: Vec<WatchProvider>,
+}
+
+impl WatchProviders {
+    pub fn categories(&self) -> [(&'static str, &[WatchProvider]); 5] {
+        [
+            ("Stream", &self.flatrate),
+            ("Free", &self.free),
+            ("With ads", &self.ads),
+            ("Rent", &self.rent),
+            ("Buy", &self.buy),
+        ]
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.categories().iter().all(|(_, providers)| providers.is_empty())
+    }
+
+    // Watchable without paying extra on any of the given services
+    pub fn is_on_any(&self, subscribed: &[u32]) -> bool {
+        self.flatrate
+            .iter()
+            .chain(&self.free)
+            .chain(&self.ads)
+            .any(|provider| subscribed.contains(&provider.provider_id))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Keyword {
-    id: usize,
-    name: String,
+    pub id: usize,
+    pub name: String,
 }
 
 #[derive(Debug)]
@@ -52,6 +252,10 @@ pub struct UserData {
     pub user_series: Vec<UserSeries>,
     pub user_movies: Vec<UserMovie>,
     pub prod_positions: Vec<ProdEntry>,
+    // Show titles in their original language instead of the metadata language
+    pub original_titles: bool,
+    // Unix time of the last full metadata refresh, lets the next one ask TMDB only for what changed
+    pub refreshed_at: Option<u64>,
 }
 #[derive(Debug, Serialize, Deserialize, Copy, Clone)]
 pub struct ProdEntry {
@@ -64,11 +268,13 @@ impl ProdEntry {
     }
 }
 
-pub fn serialize_user_productions(user_series: &[UserSeries], user_movies: &[UserMovie], prod_positions: &[ProdEntry]) -> Result<(), String> {
+pub fn serialize_user_productions(user_series: &[UserSeries], user_movies: &[UserMovie], prod_positions: &[ProdEntry], original_titles: bool, refreshed_at: Option<u64>) -> Result<(), String> {
     let john = json!({
         "series": user_series,
         "movies": user_movies,
         "positions": prod_positions,
+        "original_titles": original_titles,
+        "refreshed_at": refreshed_at,
     });
     let serialized_json = serde_json::to_string(&john).expect("Failed to serialize JSON");
     let temp_path = "res/user_prod_temp.json";
@@ -121,10 +327,15 @@ pub fn deserialize_user_productions(path: Option<String>) -> Result<UserData, St
         }
     };
 
+    let original_titles = json["original_titles"].as_bool().unwrap_or(false);
+    let refreshed_at = json["refreshed_at"].as_u64();
+
     let data = UserData {
         user_series,
         user_movies,
-        prod_positions
+        prod_positions,
+        original_titles,
+        refreshed_at,
     };
     Ok(data)
 }
@@ -164,23 +375,8 @@ pub fn fix_data_integrity(user_series: &mut Vec<UserSeries>,
 
     // #2 Ensure notes lengths
     for u_series in user_series {
-        let mut episode_counts = Vec::with_capacity(u_series.series.seasons.len());
-        let first_season_index = if u_series.series.has_specials() { 1 } else { 0 };
-        let seasons = &u_series.series.seasons;
-        // println!("Checking {} | seasons: {}", u_series.series.name, u_series.series.number_of_seasons);
-        for i in first_season_index..seasons.len() {
-            let season = &seasons[i];
-            episode_counts.push(season.episode_count as usize)
-        }
-        let story_seasons = u_series.series.number_of_seasons; // specials aren't included
-        for i in 0..story_seasons {
-            let i_index = i as usize;
-            let season_note = &mut u_series.season_notes[i_index];
-            if season_note.episode_notes.len() < episode_counts[i_index] {
-                season_note.ensure_length(episode_counts[i_index]);
-                changes += 1;
-            }
-        }
+        let story_seasons = u_series.series.story_episode_counts().len();
+        changes += u_series.fit_season_notes(story_seasons);
     }
     changes
 }
@@ -272,7 +468,7 @@ pub fn merge_data(user_series: &mut Vec<UserSeries>,
     Ok(())
 }
 
-fn merge_season_notes(notes: &mut Vec<SeasonNotes>, other_notes: &Vec<SeasonNotes>) {
+fn merge_season_notes(notes: &mut [SeasonNotes], other_notes: &[SeasonNotes]) {
     let len = notes.len();
     if len != other_notes.len() {
         eprintln!("Refusing to merge because of different note lengths");
@@ -301,12 +497,12 @@ fn pick_with_value(rating: f32, other_rating: f32) -> f32 {
     if rating == 0.0 {
         return other_rating
     }
-    return rating
+    rating
 }
 
 pub fn merge_strings(merged: &mut String, with: &str) {
     if merged.is_empty() {
-        merged.push_str(&with);
+        merged.push_str(with);
         return
     }
     if with.is_empty() || merged.starts_with(with) {
@@ -364,34 +560,49 @@ pub struct ListEntry {
 
     pub favorite: bool,
     pub watched: bool,
+    pub certification: Option<String>,
+    // Names from the genre catalog, empty until it's loaded
+    pub genres: Vec<String>,
 }
 
 impl ListEntry {
-    pub fn from_movie(user_movie: &UserMovie) -> Self {
+    pub fn from_movie(user_movie: &UserMovie, original_titles: bool) -> Self {
         let movie = &user_movie.movie;
         Self {
             production_id: EntryType::Movie(movie.id),
 
-            name: movie.title.clone(),
-            poster_path: movie.poster_path.clone(),
+            name: movie.display_title(original_titles).to_string(),
+            poster_path: user_movie.poster_path().cloned(),
             rating: movie.vote_average,
 
             favorite: user_movie.favorite,
             watched: user_movie.watched,
+            certification: user_movie.certification.clone(),
+            genres: Vec::new(),
         }
     }
 
-    pub fn from_series(user_series: &UserSeries) -> Self {
+    pub fn from_series(user_series: &UserSeries, original_titles: bool) -> Self {
         let series = &user_series.series;
         Self {
             production_id: EntryType::Series(series.id),
 
-            name: series.name.clone(),
-            poster_path: series.poster_path.clone(),
+            name: series.display_name(original_titles).to_string(),
+            poster_path: user_series.poster_path().cloned(),
             rating: series.vote_average,
 
             favorite: user_series.favorite,
             watched: user_series.watched,
+            certification: user_series.certification.clone(),
+            genres: Vec::new(),
+        }
+    }
+
+    pub fn is_same_id(a: &EntryType, b: &EntryType) -> bool {
+        match (a, b) {
+            (EntryType::Movie(a), EntryType::Movie(b)) => a == b,
+            (EntryType::Series(a), EntryType::Series(b)) => a == b,
+            _ => false,
         }
     }
 
@@ -418,6 +629,11 @@ pub struct ListFiltering {
     pub filter_favorites: bool,
     pub filter_watched: bool,
     pub filter_to_watch: bool,
+    pub filter_my_services: bool,
+    // Only entries with exactly this age rating
+    pub certification: Option<String>,
+    // Only entries with this genre, by name since movie and TV genres have different ids
+    pub genre: Option<String>,
 }
 
 impl ListFiltering {
@@ -426,6 +642,9 @@ impl ListFiltering {
             filter_favorites: false,
             filter_watched:   false,
             filter_to_watch:  false,
+            filter_my_services: false,
+            certification: None,
+            genre: None,
         }
     }
 }
@@ -0,0 +1,147 @@
+use crate::movies::Movie;
+use crate::series::Series;
+use crate::cache::unix_now;
+use crate::jobs::JobOutput;
+use crate::themoviedb::TmdbError;
+
+// Up to date metadata of a library entry, user data is left for the app to keep
+pub enum Refreshed {
+    Movie(Movie),
+    Series(Series),
+}
+
+pub struct RefreshOutcome {
+    pub refreshed: Vec<Refreshed>,
+    // Title and why it couldn't be refreshed
+    pub failed: Vec<(String, TmdbError)>,
+    // Entries TMDB reported as unchanged
+    pub skipped: usize,
+    // Whether every entry was checked, only then the library counts as refreshed
+    pub whole_library: bool,
+    // Unix time the refresh began, whatever changed after that is left for the next one
+    pub started_at: u64,
+}
+
+impl JobOutput for RefreshOutcome {
+    fn from_panic(message: String) -> Self {
+        Self {
+            refreshed: Vec::new(),
+            failed: vec![("Refresh".to_string(), TmdbError::Panicked(message))],
+            skipped: 0,
+            whole_library: false,
+            started_at: unix_now(),
+        }
+    }
+
+    fn failure(&self) -> Option<String> {
+        match self.failed.as_slice() {
+            [] => None,
+            [(title, err)] => Some(format!("{title}: {err}")),
+            failed => Some(format!("{} titles failed", failed.len())),
+        }
+    }
+}
+
+// What a library entry's user sees differently, noise like popularity and vote counts is left out
+pub fn movie_changes(old: &Movie, new: &Movie) -> Vec<String> {
+    let mut changes = Vec::new();
+    if old.title != new.title {
+        changes.push(format!("Title: {} -> {}", old.title, new.title));
+    }
+    if old.release_date != new.release_date {
+        changes.push(format!("Release date: {} -> {}", describe_date(&old.release_date), describe_date(&new.release_date)));
+    }
+    if old.overview != new.overview {
+        changes.push("Overview updated".to_string());
+    }
+    if old.poster_path != new.poster_path {
+        changes.push("New poster".to_string());
+    }
+    if old.genre_ids != new.genre_ids {
+        changes.push("Genres updated".to_string());
+    }
+    changes
+}
+
+pub fn series_changes(old: &Series, new: &Series) -> Vec<String> {
+    let mut changes = Vec::new();
+    if old.name != new.name {
+        changes.push(format!("Name: {} -> {}", old.name, new.name));
+    }
+    if old.status != new.status {
+        changes.push(format!("Status: {} -> {}", old.status, new.status));
+    }
+    if old.number_of_seasons != new.number_of_seasons {
+        changes.push(format!("Seasons: {} -> {}", old.number_of_seasons, new.number_of_seasons));
+    }
+    if old.number_of_episodes != new.number_of_episodes {
+        changes.push(format!("Episodes: {} -> {}", old.number_of_episodes, new.number_of_episodes));
+    }
+    if old.first_air_date != new.first_air_date {
+        changes.push(format!(
+            "First aired: {} -> {}",
+            describe_date(&old.first_air_date),
+            describe_date(&new.first_air_date)
+        ));
+    }
+    if old.overview != new.overview {
+        changes.push("Overview updated".to_string());
+    }
+    if old.poster_path != new.poster_path {
+        changes.push("New poster".to_string());
+    }
+    if old.genre_ids != new.genre_ids {
+        changes.push("Genres updated".to_string());
+    }
+    changes
+}
+
+fn describe_date(date: &str) -> &str {
+    if date.is_empty() {
+        "unknown"
+    } else {
+        date
+    }
+}
+
+// YYYY-MM-DD of a unix timestamp in UTC, the format /changes expects
+pub fn iso_date(unix_secs: u64) -> String {
+    // Civil from days, see http://howardhinnant.github.io/date_algorithms.html
+    let days = (unix_secs / 86400) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 { shifted_month + 3 } else { shifted_month - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_the_epoch() {
+        assert_eq!(iso_date(0), "1970-01-01");
+        assert_eq!(iso_date(86_399), "1970-01-01");
+    }
+
+    #[test]
+    fn formats_leap_days() {
+        assert_eq!(iso_date(951_782_400), "2000-02-29");
+        assert_eq!(iso_date(1_709_164_800), "2024-02-29");
+        // 2100 is not a leap year
+        assert_eq!(iso_date(4_107_542_400), "2100-03-01");
+    }
+
+    #[test]
+    fn formats_year_boundaries() {
+        assert_eq!(iso_date(1_704_067_199), "2023-12-31");
+        assert_eq!(iso_date(1_704_067_200), "2024-01-01");
+        assert_eq!(iso_date(1_735_689_599), "2024-12-31");
+        assert_eq!(iso_date(1_735_689_600), "2025-01-01");
+    }
+}
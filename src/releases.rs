@@ -0,0 +1,151 @@
+use serde::Deserialize;
+
+// Release types as numbered by TMDB
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ReleaseType {
+    Premiere,
+    TheatricalLimited,
+    Theatrical,
+    Digital,
+    Physical,
+    Tv,
+}
+
+impl ReleaseType {
+    fn from_id(id: u32) -> Option<Self> {
+        match id {
+            1 => Some(ReleaseType::Premiere),
+            2 => Some(ReleaseType::TheatricalLimited),
+            3 => Some(ReleaseType::Theatrical),
+            4 => Some(ReleaseType::Digital),
+            5 => Some(ReleaseType::Physical),
+            6 => Some(ReleaseType::Tv),
+            _ => None,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ReleaseType::Premiere => "Premiere",
+            ReleaseType::TheatricalLimited => "Theatrical (limited)",
+            ReleaseType::Theatrical => "Theatrical",
+            ReleaseType::Digital => "Digital",
+            ReleaseType::Physical => "Physical",
+            ReleaseType::Tv => "TV",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ReleaseDate {
+    pub kind: ReleaseType,
+    // YYYY-MM-DD
+    pub date: String,
+    // Festival name, distributor etc.
+    pub note: String,
+}
+
+// Age certification and releases in a single country. Series only have a certification.
+#[derive(Debug, Clone)]
+pub struct CountryRelease {
+    // ISO 3166-1
+    pub country: String,
+    pub certification: String,
+    pub releases: Vec<ReleaseDate>,
+}
+
+// The entry of the given country, matched case insensitively
+pub fn find_country<'a>(releases: &'a [CountryRelease], country: &str) -> Option<&'a CountryRelease> {
+    releases.iter().find(|release| release.country.eq_ignore_ascii_case(country))
+}
+
+// /movie/{id}/release_dates
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct MovieReleaseDates {
+    results: Vec<MovieCountryReleases>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct MovieCountryReleases {
+    iso_3166_1: String,
+    release_dates: Vec<MovieRelease>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct MovieRelease {
+    certification: String,
+    note: String,
+    // ISO 8601 timestamp
+    release_date: String,
+    #[serde(rename = "type")]
+    kind: u32,
+}
+
+impl From<MovieReleaseDates> for Vec<CountryRelease> {
+    fn from(dates: MovieReleaseDates) -> Self {
+        dates
+            .results
+            .into_iter()
+            .map(|country| {
+                // Every release carries its own certification, the theatrical one is the one people know
+                let certification = country
+                    .release_dates
+                    .iter()
+                    .filter(|release| !release.certification.trim().is_empty())
+                    .min_by_key(|release| (release.kind != 3, release.kind))
+                    .map(|release| release.certification.trim().to_string())
+                    .unwrap_or_default();
+
+                let mut releases: Vec<ReleaseDate> = country
+                    .release_dates
+                    .into_iter()
+                    .filter_map(|release| {
+                        Some(ReleaseDate {
+                            kind: ReleaseType::from_id(release.kind)?,
+                            date: release.release_date.chars().take(10).collect(),
+                            note: release.note,
+                        })
+                    })
+                    .collect();
+                releases.sort_by(|a, b| a.date.cmp(&b.date));
+
+                CountryRelease {
+                    country: country.iso_3166_1,
+                    certification,
+                    releases,
+                }
+            })
+            .collect()
+    }
+}
+
+// /tv/{id}/content_ratings
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct ContentRatings {
+    results: Vec<ContentRating>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct ContentRating {
+    iso_3166_1: String,
+    rating: String,
+}
+
+impl From<ContentRatings> for Vec<CountryRelease> {
+    fn from(ratings: ContentRatings) -> Self {
+        ratings
+            .results
+            .into_iter()
+            .map(|rating| CountryRelease {
+                country: rating.iso_3166_1,
+                certification: rating.rating.trim().to_string(),
+                releases: Vec::new(),
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,469 @@
+use std::fmt::Write;
+
+// Parameters of a TMDB search request. Built with the chained setters and turned into
+// an encoded query string with to_query_string().
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchParams {
+    pub query: String,
+    pub page: u32,
+    pub include_adult: bool,
+    // ISO 639-1 code, optionally with a country: "pl" or "pt-BR"
+    pub language: Option<String>,
+    // ISO 3166-1 code, only honored by movie searches
+    pub region: Option<String>,
+    // Release year for movies
+    pub year: Option<u32>,
+    // First air date year for series
+    pub first_air_date_year: Option<u32>,
+}
+
+#[allow(dead_code)]
+impl SearchParams {
+    pub fn new(query: impl Into<String>) -> Self {
+        Self {
+            query: query.into(),
+            page: 1,
+            include_adult: false,
+            language: None,
+            region: None,
+            year: None,
+            first_air_date_year: None,
+        }
+    }
+
+    pub fn page(mut self, page: u32) -> Self {
+        self.page = page.max(1);
+        self
+    }
+
+    pub fn include_adult(mut self, include_adult: bool) -> Self {
+        self.include_adult = include_adult;
+        self
+    }
+
+    pub fn language(mut self, language: impl Into<String>) -> Self {
+        self.language = non_empty(language.into());
+        self
+    }
+
+    pub fn region(mut self, region: impl Into<String>) -> Self {
+        self.region = non_empty(region.into());
+        self
+    }
+
+    pub fn year(mut self, year: Option<u32>) -> Self {
+        self.year = year;
+        self
+    }
+
+    pub fn first_air_date_year(mut self, year: Option<u32>) -> Self {
+        self.first_air_date_year = year;
+        self
+    }
+
+    // Everything after the '?' in the request url, every value is percent-encoded
+    // The language is left out, it replaces the configured one when the request is built
+    pub fn to_query_string(&self) -> String {
+        let mut pairs: Vec<(&str, String)> = vec![
+            ("query", self.query.trim().to_string()),
+            ("include_adult", self.include_adult.to_string()),
+            ("page", self.page.to_string()),
+        ];
+        if let Some(region) = &self.region {
+            pairs.push(("region", region.clone()));
+        }
+        if let Some(year) = self.year {
+            pairs.push(("year", year.to_string()));
+        }
+        if let Some(year) = self.first_air_date_year {
+            pairs.push(("first_air_date_year", year.to_string()));
+        }
+        encode_pairs(&pairs)
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum DiscoverSort {
+    Popularity,
+    Rating,
+    ReleaseDate,
+    VoteCount,
+}
+
+impl DiscoverSort {
+    pub const ALL: [DiscoverSort; 4] = [
+        DiscoverSort::Popularity,
+        DiscoverSort::Rating,
+        DiscoverSort::ReleaseDate,
+        DiscoverSort::VoteCount,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            DiscoverSort::Popularity => "Popularity",
+            DiscoverSort::Rating => "Rating",
+            DiscoverSort::ReleaseDate => "Release date",
+            DiscoverSort::VoteCount => "Vote count",
+        }
+    }
+
+    fn sort_by(&self, is_tv: bool) -> &'static str {
+        match (self, is_tv) {
+            (DiscoverSort::Popularity, _) => "popularity.desc",
+            (DiscoverSort::Rating, _) => "vote_average.desc",
+            (DiscoverSort::ReleaseDate, false) => "primary_release_date.desc",
+            (DiscoverSort::ReleaseDate, true) => "first_air_date.desc",
+            (DiscoverSort::VoteCount, _) => "vote_count.desc",
+        }
+    }
+}
+
+// Filters of /discover/movie and /discover/tv, the same set works for both
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiscoverParams {
+    pub page: u32,
+    pub include_adult: bool,
+    // Results have to match every genre
+    pub genres: Vec<u32>,
+    pub year_from: Option<u32>,
+    pub year_to: Option<u32>,
+    pub min_vote_average: Option<f32>,
+    pub min_vote_count: Option<u32>,
+    // Minutes, per episode for series
+    pub runtime_from: Option<u32>,
+    pub runtime_to: Option<u32>,
+    pub original_language: Option<String>,
+    pub sort: DiscoverSort,
+}
+
+#[allow(dead_code)]
+impl DiscoverParams {
+    pub fn new() -> Self {
+        Self {
+            page: 1,
+            include_adult: false,
+            genres: Vec::new(),
+            year_from: None,
+            year_to: None,
+            min_vote_average: None,
+            min_vote_count: None,
+            runtime_from: None,
+            runtime_to: None,
+            original_language: None,
+            sort: DiscoverSort::Popularity,
+        }
+    }
+
+    pub fn page(mut self, page: u32) -> Self {
+        self.page = page.max(1);
+        self
+    }
+
+    pub fn include_adult(mut self, include_adult: bool) -> Self {
+        self.include_adult = include_adult;
+        self
+    }
+
+    pub fn genres(mut self, genres: Vec<u32>) -> Self {
+        self.genres = genres;
+        self
+    }
+
+    pub fn years(mut self, from: Option<u32>, to: Option<u32>) -> Self {
+        self.year_from = from;
+        self.year_to = to;
+        self
+    }
+
+    pub fn min_vote_average(mut self, average: Option<f32>) -> Self {
+        self.min_vote_average = average;
+        self
+    }
+
+    pub fn min_vote_count(mut self, count: Option<u32>) -> Self {
+        self.min_vote_count = count;
+        self
+    }
+
+    pub fn runtime(mut self, from: Option<u32>, to: Option<u32>) -> Self {
+        self.runtime_from = from;
+        self.runtime_to = to;
+        self
+    }
+
+    pub fn original_language(mut self, language: impl Into<String>) -> Self {
+        self.original_language = non_empty(language.into());
+        self
+    }
+
+    pub fn sort(mut self, sort: DiscoverSort) -> Self {
+        self.sort = sort;
+        self
+    }
+
+    // Movies and series name their date filters differently
+    pub fn to_query_string(&self, is_tv: bool) -> String {
+        let date_key = if is_tv { "first_air_date" } else { "primary_release_date" };
+        let mut pairs: Vec<(&str, String)> = vec![
+            ("include_adult", self.include_adult.to_string()),
+            ("page", self.page.to_string()),
+            ("sort_by", self.sort.sort_by(is_tv).to_string()),
+        ];
+        if !self.genres.is_empty() {
+            let ids: Vec<String> = self.genres.iter().map(|id| id.to_string()).collect();
+            pairs.push(("with_genres", ids.join(",")));
+        }
+        let gte_key = format!("{date_key}.gte");
+        let lte_key = format!("{date_key}.lte");
+        if let Some(year) = self.year_from {
+            pairs.push((&gte_key, format!("{year}-01-01")));
+        }
+        if let Some(year) = self.year_to {
+            pairs.push((&lte_key, format!("{year}-12-31")));
+        }
+        if let Some(average) = self.min_vote_average {
+            pairs.push(("vote_average.gte", average.to_string()));
+        }
+        if let Some(count) = self.min_vote_count {
+            pairs.push(("vote_count.gte", count.to_string()));
+        }
+        if let Some(runtime) = self.runtime_from {
+            pairs.push(("with_runtime.gte", runtime.to_string()));
+        }
+        if let Some(runtime) = self.runtime_to {
+            pairs.push(("with_runtime.lte", runtime.to_string()));
+        }
+        if let Some(language) = &self.original_language {
+            pairs.push(("with_original_language", language.clone()));
+        }
+        encode_pairs(&pairs)
+    }
+}
+
+// Ids other sites use for a title, /find translates them to TMDB's
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ExternalSource {
+    Imdb,
+    Tvdb,
+}
+
+impl ExternalSource {
+    pub fn param(&self) -> &'static str {
+        match self {
+            ExternalSource::Imdb => "imdb_id",
+            ExternalSource::Tvdb => "tvdb_id",
+        }
+    }
+}
+
+// Something pasted or dropped that points at a single title instead of being a search query
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExternalLink {
+    // themoviedb.org/movie/550-fight-club
+    Tmdb { is_tv: bool, id: u32 },
+    // External id, e.g. tt0137523 alone or in an imdb.com/title/ link
+    External(ExternalSource, String),
+    // Letterboxd links carry no id, only a slug of the title that ends in a year for remakes
+    Letterboxd { title: String, year: Option<u32> },
+}
+
+// The first link in text, .url shortcut files keep theirs in a "URL=" line
+pub fn parse_external_link(text: &str) -> Option<ExternalLink> {
+    text.split_whitespace()
+        .map(|token| token.strip_prefix("URL=").unwrap_or(token))
+        .find_map(parse_link)
+}
+
+fn parse_link(link: &str) -> Option<ExternalLink> {
+    if let Some(id) = imdb_id(link) {
+        return Some(ExternalLink::External(ExternalSource::Imdb, id));
+    }
+
+    let link = link.split_once("://").map_or(link, |(_, rest)| rest);
+    let (host, path) = link.split_once('/').unwrap_or((link, ""));
+    let host = host.trim_start_matches("www.").trim_start_matches("m.");
+    let (path, query) = path.split_once('?').unwrap_or((path, ""));
+    let mut segments = path.split('/').filter(|segment| !segment.is_empty());
+
+    match host {
+        "themoviedb.org" => {
+            let is_tv = match segments.next()? {
+                "movie" => false,
+                "tv" => true,
+                _ => return None,
+            };
+            // The id is followed by a slug: 550-fight-club
+            let id = segments.next()?.split('-').next()?.parse().ok()?;
+            Some(ExternalLink::Tmdb { is_tv, id })
+        }
+        "imdb.com" => segments.find_map(imdb_id).map(|id| ExternalLink::External(ExternalSource::Imdb, id)),
+        // Only the old links have an id: thetvdb.com/?tab=series&id=121361
+        "thetvdb.com" => query
+            .split('&')
+            .find_map(|pair| pair.strip_prefix("id=")?.parse::<u32>().ok())
+            .map(|id| ExternalLink::External(ExternalSource::Tvdb, id.to_string())),
+        // letterboxd.com/film/dune-2021/, also under a user: letterboxd.com/someone/film/dune-2021/
+        "letterboxd.com" => {
+            let slug = segments.skip_while(|segment| *segment != "film").nth(1)?;
+            Some(letterboxd_title(slug))
+        }
+        _ => None,
+    }
+}
+
+fn imdb_id(text: &str) -> Option<String> {
+    let digits = text.strip_prefix("tt")?;
+    (digits.len() >= 7 && digits.bytes().all(|byte| byte.is_ascii_digit())).then(|| text.to_string())
+}
+
+fn letterboxd_title(slug: &str) -> ExternalLink {
+    let words: Vec<&str> = slug.split('-').collect();
+    let year = match words.as_slice() {
+        [_, .., last] if last.len() == 4 => last.parse().ok().filter(|year| (1870..=2100).contains(year)),
+        _ => None,
+    };
+    let title_words = if year.is_some() { &words[..words.len() - 1] } else { &words[..] };
+    ExternalLink::Letterboxd {
+        title: title_words.join(" "),
+        year,
+    }
+}
+
+fn non_empty(value: String) -> Option<String> {
+    let trimmed = value.trim();
+    if trimmed.is_empty() {
+        None
+    } else {
+        Some(trimmed.to_string())
+    }
+}
+
+pub fn encode_pairs(pairs: &[(&str, String)]) -> String {
+    let mut encoded = String::new();
+    for (key, value) in pairs {
+        if !encoded.is_empty() {
+            encoded.push('&');
+        }
+        encoded.push_str(&encode_component(key));
+        encoded.push('=');
+        encoded.push_str(&encode_component(value));
+    }
+    encoded
+}
+
+// Percent-encodes everything but the RFC 3986 unreserved characters, multi-byte characters are encoded per UTF-8 byte
+pub fn encode_component(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => encoded.push(byte as char),
+            _ => {
+                let _ = write!(encoded, "%{byte:02X}");
+            }
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_tmdb_and_imdb_links() {
+        assert_eq!(
+            parse_external_link("https://www.themoviedb.org/movie/550-fight-club?language=pl"),
+            Some(ExternalLink::Tmdb { is_tv: false, id: 550 })
+        );
+        assert_eq!(
+            parse_external_link("themoviedb.org/tv/1399"),
+            Some(ExternalLink::Tmdb { is_tv: true, id: 1399 })
+        );
+        let fight_club = Some(ExternalLink::External(ExternalSource::Imdb, "tt0137523".into()));
+        assert_eq!(parse_external_link("https://m.imdb.com/title/tt0137523/?ref_=nv_sr"), fight_club);
+        assert_eq!(parse_external_link("  tt0137523 "), fight_club);
+        assert_eq!(parse_external_link("[InternetShortcut]\nURL=https://www.imdb.com/title/tt0137523/"), fight_club);
+    }
+
+    #[test]
+    fn parses_letterboxd_slugs() {
+        assert_eq!(
+            parse_external_link("https://letterboxd.com/film/dune-2021/"),
+            Some(ExternalLink::Letterboxd { title: "dune".into(), year: Some(2021) })
+        );
+        assert_eq!(
+            parse_external_link("letterboxd.com/someone/film/1917/"),
+            Some(ExternalLink::Letterboxd { title: "1917".into(), year: None })
+        );
+    }
+
+    #[test]
+    fn ignores_plain_queries() {
+        assert_eq!(parse_external_link("fight club"), None);
+        assert_eq!(parse_external_link("tt123"), None);
+        assert_eq!(parse_external_link("https://www.themoviedb.org/person/287-brad-pitt"), None);
+    }
+
+    #[test]
+    fn encodes_reserved_characters() {
+        assert_eq!(encode_component("Fast & Furious"), "Fast%20%26%20Furious");
+        assert_eq!(encode_component("#alive"), "%23alive");
+        assert_eq!(encode_component("100% Wolf"), "100%25%20Wolf");
+        assert_eq!(encode_component("What?=+/"), "What%3F%3D%2B%2F");
+    }
+
+    #[test]
+    fn encodes_non_ascii_as_utf8() {
+        assert_eq!(encode_component("Amélie"), "Am%C3%A9lie");
+        assert_eq!(encode_component("千と千尋"), "%E5%8D%83%E3%81%A8%E5%8D%83%E5%B0%8B");
+    }
+
+    #[test]
+    fn leaves_unreserved_characters_alone() {
+        assert_eq!(encode_component("Se7en-2.0_~"), "Se7en-2.0_~");
+    }
+
+    #[test]
+    fn builds_query_string_with_optional_parameters() {
+        let params = SearchParams::new("  Léon & Mathilda ")
+            .page(3)
+            .include_adult(false)
+            .language("pl-PL")
+            .region("")
+            .year(Some(1994));
+        assert_eq!(
+            params.to_query_string(),
+            "query=L%C3%A9on%20%26%20Mathilda&include_adult=false&page=3&year=1994"
+        );
+    }
+
+    #[test]
+    fn adult_flag_follows_the_setting() {
+        assert!(SearchParams::new("x").include_adult(true).to_query_string().contains("include_adult=true"));
+        assert!(SearchParams::new("x").to_query_string().contains("include_adult=false"));
+    }
+
+    #[test]
+    fn discover_uses_the_right_date_filters() {
+        let params = DiscoverParams::new()
+            .genres(vec![18, 53])
+            .years(Some(1990), Some(1999))
+            .min_vote_average(Some(7.5))
+            .runtime(None, Some(120))
+            .original_language("ja")
+            .sort(DiscoverSort::ReleaseDate);
+        assert_eq!(
+            params.to_query_string(false),
+            "include_adult=false&page=1&sort_by=primary_release_date.desc&with_genres=18%2C53\
+             &primary_release_date.gte=1990-01-01&primary_release_date.lte=1999-12-31&vote_average.gte=7.5\
+             &with_runtime.lte=120&with_original_language=ja"
+        );
+        assert!(params.to_query_string(true).contains("first_air_date.gte=1990-01-01"));
+        assert!(params.to_query_string(true).contains("sort_by=first_air_date.desc"));
+    }
+
+    #[test]
+    fn page_is_never_zero() {
+        assert_eq!(SearchParams::new("x").page(0).page, 1);
+    }
+}
@@ -1,3 +1,6 @@
+use crate::credits::Credits;
+use crate::production::{Genre, Images, Keyword, ProductionIds, Trailer};
+use crate::releases::CountryRelease;
 use serde::{Deserialize, Serialize};
 use std::fmt::Display;
 
@@ -5,6 +8,9 @@ use std::fmt::Display;
 pub struct Series {
     pub id: u32,
     pub name: String,
+    // Name in the original language, name is localized
+    #[serde(default)]
+    pub original_name: String,
     pub original_language: String,
     pub overview: String,
     pub popularity: f32,
@@ -16,12 +22,15 @@ pub struct Series {
     pub number_of_episodes: u32,
     pub status: String,
     pub seasons: Vec<Season>,
+    #[serde(default)]
+    pub genre_ids: Vec<u32>,
 }
 impl Series {
     pub fn from(series: &SearchedSeries, details: SeriesDetails) -> Self {
         Self {
             id: series.id,
             name: series.name.clone(),
+            original_name: series.original_name.clone(),
             original_language: series.original_language.clone(),
             overview: series.overview.clone(),
             popularity: series.popularity,
@@ -29,6 +38,12 @@ impl Series {
             first_air_date: series.first_air_date.clone(),
             vote_average: series.vote_average,
             adult: series.adult,
+            // Search results always list genre ids, details are the fallback
+            genre_ids: if series.genre_ids.is_empty() {
+                details.genres.iter().map(|genre| genre.id).collect()
+            } else {
+                series.genre_ids.clone()
+            },
             number_of_seasons: details.number_of_seasons,
             number_of_episodes: details.number_of_episodes,
             status: details.status,
@@ -36,15 +51,35 @@ impl Series {
         }
     }
 
+    // Libraries saved before original names were kept fall back to the localized one
+    pub fn display_name(&self, original: bool) -> &str {
+        if original && !self.original_name.is_empty() {
+            &self.original_name
+        } else {
+            &self.name
+        }
+    }
+
     pub fn has_specials(&self) -> bool {
         self.seasons[0].season_number == 0
     }
+
+    // Episode counts of the numbered seasons, in order
+    pub fn story_episode_counts(&self) -> Vec<usize> {
+        self.seasons
+            .iter()
+            .filter(|season| season.season_number != 0)
+            .map(|season| season.episode_count as usize)
+            .collect()
+    }
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct SearchedSeries {
     pub id: u32,
     pub name: String,
+    #[serde(default)]
+    pub original_name: String,
     pub original_language: String,
     pub overview: String,
     pub popularity: f32,
@@ -52,6 +87,8 @@ pub struct SearchedSeries {
     pub first_air_date: String,
     pub vote_average: f32,
     pub adult: bool,
+    #[serde(default)]
+    pub genre_ids: Vec<u32>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -61,7 +98,13 @@ pub struct UserSeries {
     pub note: String,
     pub season_notes: Vec<SeasonNotes>,
     pub watched: bool,
-    pub favorite: bool
+    pub favorite: bool,
+    // Age rating in the configured region, None until fetched, empty if the series isn't rated there
+    #[serde(default)]
+    pub certification: Option<String>,
+    // Picked in the artwork browser, replaces the one TMDB picked
+    #[serde(default)]
+    pub custom_poster: Option<String>,
 }
 
 impl UserSeries {
@@ -79,12 +122,63 @@ impl UserSeries {
             season_notes: notes,
             watched: false,
             favorite: false,
+            certification: None,
+            custom_poster: None,
         }
     }
+
+    pub fn poster_path(&self) -> Option<&String> {
+        self.custom_poster.as_ref().or(self.series.poster_path.as_ref())
+    }
     // notes for specials end up at the end of the vector
     pub fn season_note(&mut self, season_num: u32) -> &mut SeasonNotes {
         &mut self.season_notes[season_num as usize - 1]
     }
+
+    // None for specials and episodes the notes haven't grown to yet
+    pub fn episode_note(&self, season_num: u32, episode_num: u32) -> Option<&String> {
+        let season_index = (season_num as usize).checked_sub(1)?;
+        let episode_index = (episode_num as usize).checked_sub(1)?;
+        self.season_notes.get(season_index)?.episode_notes.get(episode_index)
+    }
+
+    // Returns false for specials, there's no place for their notes yet
+    pub fn set_episode_note(&mut self, season_num: u32, episode_num: u32, note: String) -> bool {
+        if season_num == 0 || episode_num == 0 || season_num as usize > self.season_notes.len() {
+            return false;
+        }
+        let season_notes = self.season_note(season_num);
+        season_notes.ensure_length(episode_num as usize);
+        *season_notes.episode_note(episode_num) = note;
+        true
+    }
+
+    // Swaps in refreshed metadata, returns how many season notes had to be added or extended
+    pub fn update_series(&mut self, series: Series) -> usize {
+        let previous_seasons = self.series.story_episode_counts().len();
+        self.series = series;
+        self.fit_season_notes(previous_seasons)
+    }
+
+    // Makes room for every season and episode without dropping anything that was written.
+    // previous_seasons is how many numbered seasons the notes were laid out for, new ones are
+    // inserted after them so the notes for specials stay at the end.
+    pub fn fit_season_notes(&mut self, previous_seasons: usize) -> usize {
+        let mut changes = 0;
+        let episode_counts = self.series.story_episode_counts();
+        let insert_at = previous_seasons.min(self.season_notes.len());
+        for (index, &episode_count) in episode_counts.iter().enumerate().skip(insert_at) {
+            self.season_notes.insert(index, SeasonNotes::empty(episode_count));
+            changes += 1;
+        }
+        for (notes, &episode_count) in self.season_notes.iter_mut().zip(&episode_counts) {
+            if notes.episode_notes.len() < episode_count {
+                notes.ensure_length(episode_count);
+                changes += 1;
+            }
+        }
+        changes
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -95,6 +189,21 @@ pub struct SeriesDetails {
     pub status: String, //is finished?
     //pub episode_run_time: Vec<u32>, this is broken
     pub seasons: Vec<Season>,
+    #[serde(default)]
+    pub genres: Vec<Genre>,
+}
+
+// Everything the series view and the context menus need, fetched in a single request
+#[derive(Debug)]
+pub struct SeriesFullDetails {
+    pub details: SeriesDetails,
+    // Aggregated over every season
+    pub credits: Credits,
+    pub trailers: Vec<Trailer>,
+    pub keywords: Vec<Keyword>,
+    pub external_ids: ProductionIds,
+    pub images: Images,
+    pub ratings: Vec<CountryRelease>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -147,6 +256,27 @@ pub struct Episode {
     pub overview: String,
     pub runtime: Option<u32>,
     pub vote_average: f32,
+    #[serde(default)]
+    pub air_date: Option<String>,
+}
+
+// /tv/{id}/season/{s}/episode/{e}
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EpisodeDetails {
+    pub id: u32,
+    pub name: String,
+    pub overview: String,
+    pub season_number: u32,
+    pub episode_number: u32,
+    pub air_date: Option<String>,
+    pub runtime: Option<u32>,
+    pub still_path: Option<String>,
+    pub vote_average: f32,
+    pub vote_count: u32,
+    // Guest stars as the cast next to the episode's crew, filled in from "guest_stars" and "crew"
+    #[serde(skip)]
+    pub credits: Credits,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -178,3 +308,67 @@ impl SeasonNotes {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn season(season_number: u32, episode_count: u32) -> Season {
+        Season {
+            air_date: None,
+            episode_count,
+            id: season_number,
+            name: format!("Season {season_number}"),
+            overview: String::new(),
+            poster_path: None,
+            season_number,
+            vote_average: 0.0,
+        }
+    }
+
+    // Notes are laid out with the numbered seasons first and specials last
+    fn user_series(seasons: Vec<Season>) -> UserSeries {
+        let mut user_series = UserSeries::new(Series { seasons, ..Series::default() });
+        if user_series.series.has_specials() {
+            user_series.season_notes.rotate_left(1);
+        }
+        for (index, notes) in user_series.season_notes.iter_mut().enumerate() {
+            notes.note = format!("notes {index}");
+        }
+        user_series
+    }
+
+    fn episode_counts(user_series: &UserSeries) -> Vec<usize> {
+        user_series.season_notes.iter().map(|notes| notes.episode_notes.len()).collect()
+    }
+
+    #[test]
+    fn new_season_goes_before_the_specials() {
+        let mut user_series = user_series(vec![season(0, 2), season(1, 8), season(2, 10)]);
+        user_series.series.seasons.push(season(3, 6));
+
+        assert_eq!(user_series.fit_season_notes(2), 1);
+        assert_eq!(episode_counts(&user_series), [8, 10, 6, 2]);
+        assert_eq!(user_series.season_notes[2].note, "");
+        assert_eq!(user_series.season_notes[3].note, "notes 2");
+    }
+
+    #[test]
+    fn existing_season_grows() {
+        let mut user_series = user_series(vec![season(1, 8), season(2, 4)]);
+        user_series.season_notes[1].episode_notes[3] = "cliffhanger".into();
+        user_series.series.seasons[1].episode_count = 10;
+
+        assert_eq!(user_series.fit_season_notes(2), 1);
+        assert_eq!(episode_counts(&user_series), [8, 10]);
+        assert_eq!(user_series.season_notes[1].episode_notes[3], "cliffhanger");
+    }
+
+    #[test]
+    fn fitting_notes_are_left_alone() {
+        let mut user_series = user_series(vec![season(0, 3), season(1, 8), season(2, 10)]);
+
+        assert_eq!(user_series.fit_season_notes(2), 0);
+        assert_eq!(episode_counts(&user_series), [8, 10, 3]);
+    }
+}
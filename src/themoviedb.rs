@@ -1,316 +1,1347 @@
-use crate::jobs::Job;
-use crate::movies::MovieDetails;
-use crate::production::{Keyword, Production, ProductionIds, Trailer};
-use crate::series::{SeasonDetails, SeriesDetails};
-use egui::TextBuffer;
+use crate::cache::{unix_now, ResponseCache};
+use crate::config::Config;
+use crate::credits::{AggregateCredits, Credits};
+use crate::images::{Configuration, ImageConfiguration, ImageKind};
+use crate::jobs::{self, Job, JobOutput, Priority};
+use crate::limiter::RateLimiter;
+use crate::movies::{Collection, Movie, MovieFullDetails};
+use crate::person::{PersonCredit, PersonDetails, SearchedPerson};
+use crate::production::{
+    Genre, Images, Keyword, PageTotals, PagedSources, Production, ProductionPage, Trailer, WatchProvider,
+    WatchProviders,
+};
+use crate::refresh::{iso_date, RefreshOutcome, Refreshed};
+use crate::releases::{ContentRatings, CountryRelease, MovieReleaseDates};
+use crate::search::{encode_component, DiscoverParams, ExternalLink, ExternalSource, SearchParams};
+use crate::series::{EpisodeDetails, SearchedSeries, SeasonDetails, Series, SeriesDetails, SeriesFullDetails};
+use serde::de::DeserializeOwned;
 use serde_json::Value;
+use std::collections::HashSet;
+use std::fmt::{Display, Formatter};
+use std::sync::Arc;
 use std::time::Duration;
 use ureq::{Agent, AgentBuilder};
 
-const SEARCH_MULTI_URL: &str = "https://api.themoviedb.org/3/search/multi";
-const SERIES_DETAILS_URL: &str = "https://api.themoviedb.org/3/tv/"; //{series_id}
-const MOVIE_DETAILS_URL: &str = "https://api.themoviedb.org/3/movie/"; //{movie_id}
-const IMAGE_URL: &str = "https://image.tmdb.org/t/p/";
-const IMDB_TITLE: &str = "https://www.imdb.com/title/";
-const IMDB_FIND: &str = "https://www.imdb.com/find/?q=";
+pub const DEFAULT_API_URL: &str = "https://api.themoviedb.org/3";
+pub const DEFAULT_IMAGE_URL: &str = "https://image.tmdb.org/t/p/";
+const CACHE_DIR: &str = "res/cache";
+// TMDB allows around 50 requests per second, stay well below that
+const REQUESTS_PER_SECOND: usize = 20;
+const MAX_RETRIES: u32 = 3;
+// Text that TMDB leaves empty when there's no translation to the requested language
+const TRANSLATED_KEYS: [&str; 5] = ["title", "name", "overview", "tagline", "biography"];
 
-#[allow(dead_code)]
-pub enum Width {
-    Original,
-    W500,
-    W400,
-    W300,
-    W200,
+// Endpoint paths, relative to the API root
+const SEARCH_MULTI_PATH: &str = "/search/multi";
+const SEARCH_MOVIE_PATH: &str = "/search/movie";
+const SEARCH_TV_PATH: &str = "/search/tv";
+const SEARCH_PERSON_PATH: &str = "/search/person";
+const DISCOVER_MOVIE_PATH: &str = "/discover/movie";
+const DISCOVER_TV_PATH: &str = "/discover/tv";
+const TRENDING_DAY_PATH: &str = "/trending/all/day";
+const TRENDING_WEEK_PATH: &str = "/trending/all/week";
+const POPULAR_MOVIE_PATH: &str = "/movie/popular";
+const POPULAR_TV_PATH: &str = "/tv/popular";
+const TOP_RATED_MOVIE_PATH: &str = "/movie/top_rated";
+const TOP_RATED_TV_PATH: &str = "/tv/top_rated";
+const NOW_PLAYING_PATH: &str = "/movie/now_playing";
+const UPCOMING_PATH: &str = "/movie/upcoming";
+const AIRING_TODAY_PATH: &str = "/tv/airing_today";
+const ON_THE_AIR_PATH: &str = "/tv/on_the_air";
+const GENRE_MOVIE_PATH: &str = "/genre/movie/list";
+const GENRE_TV_PATH: &str = "/genre/tv/list";
+const SERIES_PATH: &str = "/tv"; // /{series_id}
+const MOVIE_PATH: &str = "/movie"; // /{movie_id}
+const PERSON_PATH: &str = "/person"; // /{person_id}
+const COLLECTION_PATH: &str = "/collection"; // /{collection_id}
+const PROVIDERS_MOVIE_PATH: &str = "/watch/providers/movie";
+const PROVIDERS_TV_PATH: &str = "/watch/providers/tv";
+const CONFIGURATION_PATH: &str = "/configuration";
+const FIND_PATH: &str = "/find"; // /{external_id}
+const MOVIE_CHANGES_PATH: &str = "/movie/changes";
+const TV_CHANGES_PATH: &str = "/tv/changes";
+// Sub-requests bundled into the details call
+const MOVIE_APPENDS: &str = "credits,videos,keywords,external_ids,images,release_dates";
+// Aggregate credits cover every season, plain credits would only list the latest one
+const SERIES_APPENDS: &str = "aggregate_credits,videos,keywords,external_ids,images,content_ratings";
+// TMDB ignores anything past 20 appended sub-requests
+const MAX_APPENDS: usize = 20;
+// TMDB keeps two weeks of changes, an older refresh has to check everything
+const MAX_CHANGES_AGE_SECS: u64 = 14 * 24 * 60 * 60;
+// Past this many pages of changes it's cheaper to refresh everything
+const MAX_CHANGES_PAGES: u32 = 20;
+
+#[derive(Debug, Clone)]
+pub enum TmdbError {
+    // Connection refused, DNS failure, timeout etc.
+    Transport(String),
+    // 401, the access token is missing, malformed or revoked
+    Unauthorized(String),
+    // 429, seconds to wait if the server told us
+    RateLimited(Option<u64>),
+    // Any other non 2xx response
+    Status(u16, String),
+    // The body didn't have the shape we expected
+    Decode(String),
+    // Local file system failures (downloads)
+    Io(String),
+    // The job doing the request panicked
+    Panicked(String),
+}
+
+impl Display for TmdbError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Transport(msg) => write!(f, "Network error: {msg}"),
+            Self::Unauthorized(msg) => write!(f, "TMDB rejected the access token: {msg}"),
+            Self::RateLimited(Some(secs)) => write!(f, "Rate limited by TMDB, retry in {secs}s"),
+            Self::RateLimited(None) => write!(f, "Rate limited by TMDB"),
+            Self::Status(code, msg) => write!(f, "TMDB responded with {code}: {msg}"),
+            Self::Decode(msg) => write!(f, "Unexpected response from TMDB: {msg}"),
+            Self::Io(msg) => write!(f, "IO error: {msg}"),
+            Self::Panicked(msg) => write!(f, "Internal error: {msg}"),
+        }
+    }
+}
+
+impl std::error::Error for TmdbError {}
+
+impl From<ureq::Error> for TmdbError {
+    fn from(err: ureq::Error) -> Self {
+        match err {
+            ureq::Error::Status(code, response) => {
+                let retry_after = response.header("Retry-After").and_then(|secs| secs.trim().parse().ok());
+                // TMDB explains most failures in a {"status_code", "status_message"} object
+                let message = match serde_json::from_reader::<_, Value>(response.into_reader()) {
+                    Ok(body) => match body["status_message"].as_str() {
+                        Some(msg) => msg.to_string(),
+                        None => body.to_string(),
+                    },
+                    Err(_) => String::from("no message"),
+                };
+                match code {
+                    401 => Self::Unauthorized(message),
+                    429 => Self::RateLimited(retry_after),
+                    _ => Self::Status(code, message),
+                }
+            }
+            ureq::Error::Transport(transport) => Self::Transport(transport.to_string()),
+        }
+    }
+}
+
+impl From<serde_json::Error> for TmdbError {
+    fn from(err: serde_json::Error) -> Self {
+        Self::Decode(err.to_string())
+    }
+}
+
+impl From<std::io::Error> for TmdbError {
+    fn from(err: std::io::Error) -> Self {
+        Self::Io(err.to_string())
+    }
+}
+
+pub type TmdbResult<T> = Result<T, TmdbError>;
+
+impl<T: Send + 'static> JobOutput for TmdbResult<T> {
+    fn from_panic(message: String) -> Self {
+        Err(TmdbError::Panicked(message))
+    }
+
+    fn failure(&self) -> Option<String> {
+        self.as_ref().err().map(|err| err.to_string())
+    }
+}
+
+// A prepared GET that can be moved into a job. Goes through the response cache when one is attached.
+pub struct TmdbRequest {
+    request: ureq::Request,
+    // The same request in the fallback language, sent only when something wasn't translated
+    fallback: Option<ureq::Request>,
+    cache: Option<Arc<ResponseCache>>,
+    // Skips cached responses, the new one is still cached
+    fresh: bool,
+    limiter: Arc<RateLimiter>,
+}
+
+impl TmdbRequest {
+    pub fn query(mut self, param: &str, value: &str) -> Self {
+        self.request = self.request.query(param, value);
+        self.fallback = self.fallback.map(|fallback| fallback.query(param, value));
+        self
+    }
+
+    pub fn fresh(mut self) -> Self {
+        self.fresh = true;
+        self
+    }
+
+    // Sends the request and deserializes the body, every failure along the way ends up as a TmdbError
+    pub fn fetch_json<T: DeserializeOwned>(&self) -> TmdbResult<T> {
+        let body = self.fetch_string()?;
+        let mut value: Value = serde_json::from_str(&body)?;
+        if let Some(fallback) = &self.fallback {
+            if has_untranslated_text(&value) {
+                let fallback = TmdbRequest {
+                    request: fallback.clone(),
+                    fallback: None,
+                    cache: self.cache.clone(),
+                    fresh: self.fresh,
+                    limiter: self.limiter.clone(),
+                };
+                // Missing translations aren't worth failing the whole request over
+                match fallback.fetch_json::<Value>() {
+                    Ok(translated) => fill_untranslated_text(&mut value, &translated),
+                    Err(err) => eprintln!("Request in the fallback language failed: {err}"),
+                }
+            }
+        }
+        Ok(serde_json::from_value(value)?)
+    }
+
+    pub fn fetch_string(&self) -> TmdbResult<String> {
+        let url = self.request.url().to_string();
+        if let Some(body) = self.cache.as_ref().filter(|_| !self.fresh).and_then(|cache| cache.get(&url)) {
+            return Ok(body);
+        }
+
+        match self.call_throttled() {
+            Ok(response) => {
+                let body = response.into_string().map_err(|err| TmdbError::Transport(err.to_string()))?;
+                if let Some(cache) = &self.cache {
+                    cache.put(&url, &body);
+                }
+                Ok(body)
+            }
+            // We're most likely offline, an outdated response is better than nothing
+            // unless the caller asked for a fresh one, a refresh must not report stale data as refreshed
+            Err(TmdbError::Transport(msg)) => {
+                let stale = self.cache.as_ref().filter(|_| !self.fresh).and_then(|cache| cache.get_stale(&url));
+                stale.ok_or(TmdbError::Transport(msg))
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    // Every request waits for a token from the shared limiter. A 429 pauses the limiter for everyone
+    // (honoring Retry-After when present, otherwise backing off exponentially) before trying again.
+    fn call_throttled(&self) -> TmdbResult<ureq::Response> {
+        let mut attempt = 0;
+        loop {
+            self.limiter.acquire();
+            match self.request.clone().call() {
+                Ok(response) => return Ok(response),
+                Err(err) => match TmdbError::from(err) {
+                    TmdbError::RateLimited(retry_after) if attempt < MAX_RETRIES => {
+                        let backoff = retry_after.unwrap_or(1 << attempt);
+                        eprintln!("Rate limited, retrying in {backoff}s");
+                        self.limiter.pause_for(Duration::from_secs(backoff));
+                        attempt += 1;
+                    }
+                    err => return Err(err),
+                },
+            }
+        }
+    }
+}
+
+fn has_untranslated_text(json: &Value) -> bool {
+    match json {
+        Value::Object(map) => {
+            TRANSLATED_KEYS.iter().any(|key| map.get(*key).is_some_and(|text| text.as_str() == Some("")))
+                || map.values().any(has_untranslated_text)
+        }
+        Value::Array(values) => values.iter().any(has_untranslated_text),
+        _ => false,
+    }
+}
+
+// Copies text missing from target out of the same response in another language, objects are paired by id
+fn fill_untranslated_text(target: &mut Value, translated: &Value) {
+    match (target, translated) {
+        (Value::Object(target), Value::Object(translated)) => {
+            if target.get("id") != translated.get("id") {
+                return;
+            }
+            for key in TRANSLATED_KEYS {
+                let Some(text) = translated.get(key).and_then(Value::as_str) else {
+                    continue;
+                };
+                if let Some(value) = target.get_mut(key) {
+                    if value.as_str() == Some("") {
+                        *value = Value::from(text);
+                    }
+                }
+            }
+            for (key, value) in target.iter_mut() {
+                if let Some(translated) = translated.get(key) {
+                    fill_untranslated_text(value, translated);
+                }
+            }
+        }
+        (Value::Array(target), Value::Array(translated)) => {
+            for (value, translated) in target.iter_mut().zip(translated) {
+                fill_untranslated_text(value, translated);
+            }
+        }
+        _ => {}
+    }
+}
+
+// "pl-PL" -> "pl", the form image and video filters expect
+fn language_code(language: &str) -> &str {
+    language.split('-').next().unwrap_or(language)
+}
+
+// Appended responses are missing when the server ignores append_to_response (mirrors, the mock server)
+fn take_appended<T: DeserializeOwned + Default>(payload: &mut Value, key: &str) -> TmdbResult<T> {
+    match payload[key].take() {
+        Value::Null => Ok(T::default()),
+        value => Ok(serde_json::from_value(value)?),
+    }
+}
+
+// Only trailers are kept out of all the videos
+fn parse_trailers(mut videos: Value) -> TmdbResult<Vec<Trailer>> {
+    if videos.is_null() {
+        return Ok(Vec::new());
+    }
+    let mut trailers = Vec::new();
+    for video in take_array(&mut videos, "results")? {
+        if video["type"] == "Trailer" {
+            trailers.push(serde_json::from_value(video)?);
+        }
+    }
+    Ok(trailers)
+}
+
+// Movies list keywords under "keywords", series under "results"
+fn parse_keywords(mut keywords: Value, array_key: &str) -> TmdbResult<Vec<Keyword>> {
+    if keywords.is_null() {
+        return Ok(Vec::new());
+    }
+    let keywords = take_array(&mut keywords, array_key)?;
+    Ok(serde_json::from_value(Value::Array(keywords))?)
+}
+
+fn parse_movie_full_details(mut payload: Value) -> TmdbResult<MovieFullDetails> {
+    let credits = take_appended(&mut payload, "credits")?;
+    let trailers = parse_trailers(payload["videos"].take())?;
+    let keywords = parse_keywords(payload["keywords"].take(), "keywords")?;
+    let external_ids = take_appended(&mut payload, "external_ids")?;
+    let images = take_appended(&mut payload, "images")?;
+    let releases: MovieReleaseDates = take_appended(&mut payload, "release_dates")?;
+    Ok(MovieFullDetails {
+        details: serde_json::from_value(payload)?,
+        credits,
+        trailers,
+        keywords,
+        external_ids,
+        images,
+        releases: releases.into(),
+    })
+}
+
+fn parse_series_full_details(mut payload: Value) -> TmdbResult<SeriesFullDetails> {
+    let credits: AggregateCredits = take_appended(&mut payload, "aggregate_credits")?;
+    let trailers = parse_trailers(payload["videos"].take())?;
+    let keywords = parse_keywords(payload["keywords"].take(), "results")?;
+    let external_ids = take_appended(&mut payload, "external_ids")?;
+    let images = take_appended(&mut payload, "images")?;
+    let ratings: ContentRatings = take_appended(&mut payload, "content_ratings")?;
+    Ok(SeriesFullDetails {
+        details: serde_json::from_value(payload)?,
+        credits: credits.into(),
+        trailers,
+        keywords,
+        external_ids,
+        images,
+        ratings: ratings.into(),
+    })
+}
+
+// /movie/{id} has everything a library Movie keeps, only genres come as objects instead of ids
+fn parse_movie(mut payload: Value) -> TmdbResult<Movie> {
+    let genres: Vec<Genre> = serde_json::from_value(payload["genres"].take()).unwrap_or_default();
+    let mut movie: Movie = serde_json::from_value(payload)?;
+    movie.genre_ids = genres.iter().map(|genre| genre.id).collect();
+    Ok(movie)
+}
+
+fn parse_series(payload: Value) -> TmdbResult<Series> {
+    let searched: SearchedSeries = serde_json::from_value(payload.clone())?;
+    let details: SeriesDetails = serde_json::from_value(payload)?;
+    Ok(Series::from(&searched, details))
+}
+
+// /find lists matches per media type, an id belongs to a single title
+fn parse_find_results(mut payload: Value) -> TmdbResult<Option<Production>> {
+    for (key, media_type) in [("movie_results", "movie"), ("tv_results", "tv")] {
+        if let Some(found) = take_array(&mut payload, key)?.into_iter().next() {
+            return parse_production(found, Some(media_type)).transpose();
+        }
+    }
+    Ok(None)
+}
+
+fn take_array(json: &mut Value, key: &str) -> TmdbResult<Vec<Value>> {
+    match json[key].take() {
+        Value::Array(arr) => Ok(arr),
+        _ => Err(TmdbError::Decode(format!("\"{key}\" is not an array"))),
+    }
+}
+
+// Parses a paginated listing. Single-type endpoints (/search/movie) don't tag their results so
+// media_type is used for entries without a "media_type" key. Entries that fail to parse are skipped.
+fn parse_production_page(mut payload: Value, media_type: Option<&str>) -> TmdbResult<ProductionPage> {
+    let list = take_array(&mut payload, "results")?;
+    let mut productions = Vec::with_capacity(list.len());
+
+    for prod_obj in list {
+        match parse_production(prod_obj, media_type) {
+            Some(Ok(production)) => productions.push(production),
+            Some(Err(err)) => eprintln!("Skipping a result: {err}"),
+            None => {}
+        }
+    }
+
+    Ok(ProductionPage {
+        productions,
+        page: payload["page"].as_u64().unwrap_or(1) as u32,
+        total_pages: payload["total_pages"].as_u64().unwrap_or(1) as u32,
+        total_results: payload["total_results"].as_u64().unwrap_or(0) as u32,
+        sources: None,
+    })
 }
 
+// None for media types we don't handle
+fn parse_production(mut prod_obj: Value, default_media_type: Option<&str>) -> Option<TmdbResult<Production>> {
+    let media_type = match prod_obj["media_type"].as_str() {
+        Some(media_type) => media_type.to_string(),
+        None => default_media_type?.to_string(),
+    };
+
+    let production = match media_type.as_str() {
+        "tv" => serde_json::from_value(prod_obj).map(Production::SearchedSeries),
+        "movie" => serde_json::from_value(prod_obj).map(Production::Movie),
+        "person" => {
+            let known_for = match prod_obj["known_for"].take() {
+                Value::Array(works) => works,
+                _ => Vec::new(),
+            };
+            serde_json::from_value(prod_obj).map(|mut person: SearchedPerson| {
+                person.known_for = known_for
+                    .into_iter()
+                    .filter_map(|work| parse_production(work, None)?.ok())
+                    .collect();
+                Production::Person(person)
+            })
+        }
+        _ => return None,
+    };
+    Some(production.map_err(TmdbError::from))
+}
+
+#[derive(Clone)]
 pub struct TheMovieDB {
     access_token: String,
     agent: Agent,
-    // pub use_cache: bool,
-    // cache object outputs to avoid making multiple requests for the same data
-    // query_to_prod: VecMap<String, Vec<Production>>,
+    // Roots are configurable so a mirror, a proxy or the mock server (src/bin/mock_tmdb.rs) can stand in for TMDB
+    api_url: String,
+    image_url: String,
+    // Raw responses are cached on disk to avoid making multiple requests for the same data
+    cache: Arc<ResponseCache>,
+    pub use_cache: bool,
+    // Country whose watch providers are fetched
+    pub region: String,
+    // Metadata language and the one used where there's no translation, empty leaves it up to TMDB
+    pub language: String,
+    pub fallback_language: String,
+    // Image sizes TMDB offers, the documented ones until /configuration arrives
+    pub image_configuration: ImageConfiguration,
+    // Physical pixels per UI point, images are picked for the actual screen resolution
+    pub image_scale: f32,
+    limiter: Arc<RateLimiter>,
 }
 
 #[allow(dead_code)]
 impl TheMovieDB {
-    pub fn new(key: String, _use_cache: bool) -> Self {
+    pub fn new(config: &Config) -> Self {
+        let mut image_url = config.image_url.clone();
+        if !image_url.ends_with('/') {
+            image_url.push('/');
+        }
         Self {
-            access_token: key,
+            access_token: config.access_token.clone(),
             agent: AgentBuilder::new().timeout(Duration::from_secs(15)).build(),
-            // use_cache,
-            // query_to_prod: VecMap::new(),
+            api_url: config.api_url.trim_end_matches('/').to_string(),
+            image_url,
+            cache: Arc::new(ResponseCache::new(CACHE_DIR, config.max_cache_size_mb * 1024 * 1024)),
+            use_cache: config.enable_cache,
+            region: config.region.clone(),
+            language: config.language.clone(),
+            fallback_language: config.fallback_language.clone(),
+            image_configuration: ImageConfiguration::default(),
+            image_scale: 1.0,
+            limiter: Arc::new(RateLimiter::new(REQUESTS_PER_SECOND, Duration::from_secs(1))),
         }
     }
 
-    fn new_authorized_get(&self, url: &str) -> ureq::Request {
-        self.agent
-            .get(url)
+    // Picks up an edited config without replacing the cache and limiter that running jobs share.
+    // The image configuration and genres are fetched again by the caller.
+    pub fn apply_config(&mut self, config: &Config) {
+        let mut image_url = config.image_url.clone();
+        if !image_url.ends_with('/') {
+            image_url.push('/');
+        }
+        self.access_token = config.access_token.clone();
+        self.api_url = config.api_url.trim_end_matches('/').to_string();
+        self.image_url = image_url;
+        self.cache.set_max_size(config.max_cache_size_mb * 1024 * 1024);
+        self.use_cache = config.enable_cache;
+        self.region = config.region.clone();
+        self.language = config.language.clone();
+        self.fallback_language = config.fallback_language.clone();
+    }
+
+    // path is relative to the API root and should start with a slash
+    fn new_authorized_get(&self, path: &str) -> TmdbRequest {
+        self.localized_get(path, &self.language)
+    }
+
+    // Search requests may ask for another language than the configured one
+    fn search_get(&self, path: &str, params: &SearchParams) -> TmdbRequest {
+        let language = params.language.as_deref().unwrap_or(&self.language);
+        self.localized_get(&format!("{path}?{}", params.to_query_string()), language)
+    }
+
+    fn localized_get(&self, path: &str, language: &str) -> TmdbRequest {
+        let mut request = self
+            .agent
+            .get(&format!("{}{path}", self.api_url))
             .set("Accept", "application/json")
-            .set("Authorization", &format!("Bearer {}", self.access_token))
+            .set("Authorization", &format!("Bearer {}", self.access_token));
+        let mut fallback = None;
+        if !language.is_empty() {
+            if !self.fallback_language.is_empty() && self.fallback_language != language {
+                fallback = Some(request.clone().query("language", &self.fallback_language));
+            }
+            request = request.query("language", language);
+        }
+        let cache = self.use_cache.then(|| self.cache.clone());
+        TmdbRequest {
+            request,
+            fallback,
+            cache,
+            fresh: false,
+            limiter: self.limiter.clone(),
+        }
     }
 
-    pub fn search_production(&mut self, query: String) -> Job<Vec<Production>> {
-        let url = format!("{SEARCH_MULTI_URL}?query={query}&include_adult={}", true);
-        let request = self.new_authorized_get(&url);
+    // Languages to keep when filtering images and videos, textless images are always wanted
+    fn media_languages(&self, textless: bool) -> String {
+        let mut languages = vec![language_code(&self.language), language_code(&self.fallback_language), "en"];
+        languages.retain(|code| !code.is_empty());
+        languages.dedup();
+        if textless {
+            languages.push("null");
+        }
+        languages.join(",")
+    }
 
-        Job::new(move || {
+    // Requests blocked on the rate limiter
+    pub fn queued_requests(&self) -> usize {
+        self.limiter.queue_depth()
+    }
+
+    pub fn is_rate_limited(&self) -> bool {
+        self.limiter.is_paused()
+    }
+
+    pub fn cache(&self) -> &ResponseCache {
+        &self.cache
+    }
+
+    // sources comes from the previous page, it's only used when searching by year
+    pub fn search_production(
+        &self,
+        params: &SearchParams,
+        sources: Option<PagedSources>,
+    ) -> Job<TmdbResult<ProductionPage>> {
+        if params.year.is_some() || params.first_air_date_year.is_some() {
+            return self.search_production_by_year(params, sources);
+        }
+        let request = self.search_get(SEARCH_MULTI_PATH, params);
+
+        Job::with_priority(Priority::High, "search_production", move || {
             println!("Executing request in search_production");
-            let response = match request.call() {
-                Ok(success) => {
-                    success
-                }
-                Err(err) => {
-                    println!("{}", err.to_string());
-                    return Vec::new();
-                }
+            let payload: Value = request.fetch_json()?;
+            parse_production_page(payload, None)
+        })
+    }
+
+    // /search/multi ignores year filters, so movies and series are searched separately. Page n of the
+    // merged listing is page n of each, a listing that has no page n anymore isn't requested.
+    fn search_production_by_year(
+        &self,
+        params: &SearchParams,
+        sources: Option<PagedSources>,
+    ) -> Job<TmdbResult<ProductionPage>> {
+        let year = params.year.or(params.first_air_date_year);
+        let page = params.page;
+        let has_page = |totals: PageTotals| page <= totals.total_pages;
+        let movie_request = match sources {
+            Some(sources) if !has_page(sources.movies) => Err(sources.movies),
+            _ => Ok(self.search_get(SEARCH_MOVIE_PATH, &params.clone().year(year).first_air_date_year(None))),
+        };
+        let tv_request = match sources {
+            Some(sources) if !has_page(sources.series) => Err(sources.series),
+            _ => Ok(self.search_get(SEARCH_TV_PATH, &params.clone().year(None).first_air_date_year(year))),
+        };
+
+        Job::with_priority(Priority::High, "search_production", move || {
+            println!("Executing request in search_production");
+            let mut productions = Vec::new();
+            let mut fetch = |request: &Result<TmdbRequest, PageTotals>, media_type| -> TmdbResult<PageTotals> {
+                let request = match request {
+                    Ok(request) => request,
+                    Err(exhausted) => return Ok(*exhausted),
+                };
+                let fetched = parse_production_page(request.fetch_json()?, Some(media_type))?;
+                productions.extend(fetched.productions);
+                Ok(PageTotals {
+                    total_pages: fetched.total_pages,
+                    total_results: fetched.total_results,
+                })
             };
+            let movies = fetch(&movie_request, "movie")?;
+            let series = fetch(&tv_request, "tv")?;
+            Ok(ProductionPage {
+                productions,
+                page,
+                total_pages: movies.total_pages.max(series.total_pages),
+                total_results: movies.total_results + series.total_results,
+                sources: Some(PagedSources { movies, series }),
+            })
+        })
+    }
 
-            let json_response = response.into_string().unwrap().take();
+    pub fn search_movie(&self, params: &SearchParams) -> Job<TmdbResult<ProductionPage>> {
+        self.search_typed(SEARCH_MOVIE_PATH, params, "movie")
+    }
 
-            let mut payload: Value = serde_json::from_str(json_response.as_str()).unwrap();
-            let mut arr: Value = payload["results"].take();
-            if !arr.is_array() {
-                eprintln!("Results are not in an array");
-                return Vec::new();
-            }
-            let list = arr.as_array_mut().unwrap();
-            let mut productions = Vec::with_capacity(list.len());
-
-            for prod_obj in list {
-                let media_type = &prod_obj["media_type"];
-                if media_type == "tv" {
-                    let searched_series = serde_json::from_value(prod_obj.take()).unwrap();
-                    productions.push(Production::SearchedSeries(searched_series));
-                } else if media_type == "movie" {
-                    let movie = serde_json::from_value(prod_obj.take()).unwrap();
-                    productions.push(Production::Movie(movie));
-                }
-            }
+    pub fn search_tv(&self, params: &SearchParams) -> Job<TmdbResult<ProductionPage>> {
+        self.search_typed(SEARCH_TV_PATH, params, "tv")
+    }
+
+    pub fn search_person(&self, params: &SearchParams) -> Job<TmdbResult<ProductionPage>> {
+        self.search_typed(SEARCH_PERSON_PATH, params, "person")
+    }
 
-            productions
+    fn search_typed(&self, path: &str, params: &SearchParams, media_type: &'static str) -> Job<TmdbResult<ProductionPage>> {
+        let request = self.search_get(path, params);
+
+        Job::with_priority(Priority::High, format!("search_{media_type}"), move || {
+            println!("Executing request in search_{media_type}");
+            let payload: Value = request.fetch_json()?;
+            parse_production_page(payload, Some(media_type))
         })
     }
 
-    pub fn get_full_poster_url(poster: &str, width: Width) -> String {
-        let size = match width {
-            Width::W200 => "w200",
-            Width::W300 => "w300",
-            Width::W400 => "w400",
-            Width::W500 => "w500",
-            Width::Original => "original",
-        };
-        format!("{IMAGE_URL}{size}{poster}")
+    pub fn discover_movies(&self, params: &DiscoverParams) -> Job<TmdbResult<ProductionPage>> {
+        let url = format!("{DISCOVER_MOVIE_PATH}?{}", params.to_query_string(false));
+        let request = self.new_authorized_get(&url);
+
+        Job::with_priority(Priority::High, "discover_movies", move || {
+            println!("Executing request in discover_movies");
+            let payload: Value = request.fetch_json()?;
+            parse_production_page(payload, Some("movie"))
+        })
     }
 
-    pub fn get_series_details(&self, id: u32) -> Job<SeriesDetails> {
-        let url = format!("{SERIES_DETAILS_URL}{id}");
+    pub fn discover_tv(&self, params: &DiscoverParams) -> Job<TmdbResult<ProductionPage>> {
+        let url = format!("{DISCOVER_TV_PATH}?{}", params.to_query_string(true));
         let request = self.new_authorized_get(&url);
 
-        Job::new(move || {
-            println!("Executing request in get_series_details");
+        Job::with_priority(Priority::High, "discover_tv", move || {
+            println!("Executing request in discover_tv");
+            let payload: Value = request.fetch_json()?;
+            parse_production_page(payload, Some("tv"))
+        })
+    }
 
-            let Ok(response) = request.call() else {
-                panic!("Error on sending request");
-            };
+    pub fn get_trending(&self, this_week: bool, page: u32) -> Job<TmdbResult<ProductionPage>> {
+        let path = if this_week { TRENDING_WEEK_PATH } else { TRENDING_DAY_PATH };
+        // Trending mixes movies, series and people, every entry carries its media_type
+        self.get_feed(path, page, None)
+    }
 
-            serde_json::from_reader(response.into_reader()).unwrap()
+    pub fn get_popular(&self, is_tv: bool, page: u32) -> Job<TmdbResult<ProductionPage>> {
+        if is_tv {
+            self.get_feed(POPULAR_TV_PATH, page, Some("tv"))
+        } else {
+            self.get_feed(POPULAR_MOVIE_PATH, page, Some("movie"))
+        }
+    }
+
+    pub fn get_top_rated(&self, is_tv: bool, page: u32) -> Job<TmdbResult<ProductionPage>> {
+        if is_tv {
+            self.get_feed(TOP_RATED_TV_PATH, page, Some("tv"))
+        } else {
+            self.get_feed(TOP_RATED_MOVIE_PATH, page, Some("movie"))
+        }
+    }
+
+    pub fn get_now_playing(&self, page: u32) -> Job<TmdbResult<ProductionPage>> {
+        self.get_feed(NOW_PLAYING_PATH, page, Some("movie"))
+    }
+
+    pub fn get_upcoming(&self, page: u32) -> Job<TmdbResult<ProductionPage>> {
+        self.get_feed(UPCOMING_PATH, page, Some("movie"))
+    }
+
+    pub fn get_airing_today(&self, page: u32) -> Job<TmdbResult<ProductionPage>> {
+        self.get_feed(AIRING_TODAY_PATH, page, Some("tv"))
+    }
+
+    pub fn get_on_the_air(&self, page: u32) -> Job<TmdbResult<ProductionPage>> {
+        self.get_feed(ON_THE_AIR_PATH, page, Some("tv"))
+    }
+
+    fn get_feed(&self, path: &'static str, page: u32, media_type: Option<&'static str>) -> Job<TmdbResult<ProductionPage>> {
+        let url = format!("{path}?page={}", page.max(1));
+        let request = self.new_authorized_get(&url);
+
+        Job::new(format!("get_feed for {path}"), move || {
+            println!("Executing request in get_feed for {path}");
+            let payload: Value = request.fetch_json()?;
+            parse_production_page(payload, media_type)
         })
     }
-    pub fn get_series_details_now(&self, id: u32) -> SeriesDetails {
-        let url = format!("{SERIES_DETAILS_URL}{id}");
+
+    pub fn get_recommendations(&self, is_tv: bool, id: u32, page: u32) -> Job<TmdbResult<ProductionPage>> {
+        self.get_related(is_tv, id, "recommendations", page)
+    }
+
+    pub fn get_similar(&self, is_tv: bool, id: u32, page: u32) -> Job<TmdbResult<ProductionPage>> {
+        self.get_related(is_tv, id, "similar", page)
+    }
+
+    fn get_related(&self, is_tv: bool, id: u32, endpoint: &'static str, page: u32) -> Job<TmdbResult<ProductionPage>> {
+        let (path, media_type) = if is_tv { (SERIES_PATH, "tv") } else { (MOVIE_PATH, "movie") };
+        let url = format!("{path}/{id}/{endpoint}?page={}", page.max(1));
         let request = self.new_authorized_get(&url);
 
-        println!("Executing request in get_series_details_now");
+        Job::new(format!("get_related for {endpoint}"), move || {
+            println!("Executing request in get_related for {endpoint}");
+            let payload: Value = request.fetch_json()?;
+            parse_production_page(payload, Some(media_type))
+        })
+    }
 
-        let Ok(response) = request.call() else {
-            panic!("Error on sending request");
-        };
+    pub fn get_genres(&self, is_tv: bool) -> Job<TmdbResult<Vec<Genre>>> {
+        let path = if is_tv { GENRE_TV_PATH } else { GENRE_MOVIE_PATH };
+        let request = self.new_authorized_get(path);
 
-        serde_json::from_reader(response.into_reader()).unwrap()
+        Job::new("get_genres", move || {
+            println!("Executing request in get_genres");
+            let mut payload: Value = request.fetch_json()?;
+            let genres = take_array(&mut payload, "genres")?;
+            let mut parsed = Vec::with_capacity(genres.len());
+            for genre in genres {
+                parsed.push(serde_json::from_value(genre)?);
+            }
+            Ok(parsed)
+        })
+    }
+
+    // width is how wide the image is drawn, in UI points
+    pub fn get_image_url(&self, path: &str, kind: ImageKind, width: f32) -> String {
+        let size = self.image_configuration.best_size(kind, width * self.image_scale);
+        format!("{}{size}{path}", self.image_url)
+    }
+
+    pub fn get_original_image_url(&self, path: &str) -> String {
+        format!("{}original{path}", self.image_url)
+    }
+
+    // Only the sizes are used, the base url stays configurable
+    pub fn get_image_configuration(&self) -> Job<TmdbResult<ImageConfiguration>> {
+        let request = self.new_authorized_get(CONFIGURATION_PATH);
+
+        Job::new("get_image_configuration", move || {
+            println!("Executing request in get_image_configuration");
+            let configuration: Configuration = request.fetch_json()?;
+            Ok(configuration.images)
+        })
     }
 
-    pub fn get_season_details(&self, series_id: u32, season_number: u32) -> Job<SeasonDetails> {
-        let url = format!("{SERIES_DETAILS_URL}{series_id}/season/{season_number}");
+    pub fn get_series_details(&self, id: u32) -> Job<TmdbResult<SeriesDetails>> {
+        let url = format!("{SERIES_PATH}/{id}");
         let request = self.new_authorized_get(&url);
 
-        Job::new(move || {
+        Job::new("get_series_details", move || {
+            println!("Executing request in get_series_details");
+            request.fetch_json()
+        })
+    }
+
+    pub fn get_season_details(&self, series_id: u32, season_number: u32) -> Job<TmdbResult<SeasonDetails>> {
+        let url = format!("{SERIES_PATH}/{series_id}/season/{season_number}");
+        let request = self.new_authorized_get(&url);
+
+        Job::new("get_season_details", move || {
             println!("Executing request in get_season_details");
-            let Ok(response) = request.call() else {
-                panic!("Error on sending request");
+            request.fetch_json()
+        })
+    }
+
+    pub fn get_episode_details(&self, series_id: u32, season_number: u32, episode_number: u32) -> Job<TmdbResult<EpisodeDetails>> {
+        let url = format!("{SERIES_PATH}/{series_id}/season/{season_number}/episode/{episode_number}");
+        let request = self.new_authorized_get(&url);
+
+        Job::new("get_episode_details", move || {
+            println!("Executing request in get_episode_details");
+            let mut payload: Value = request.fetch_json()?;
+            let credits = Credits {
+                id: payload["id"].as_u64().unwrap_or(0) as u32,
+                cast: take_appended(&mut payload, "guest_stars")?,
+                crew: take_appended(&mut payload, "crew")?,
             };
+            let mut episode: EpisodeDetails = serde_json::from_value(payload)?;
+            episode.credits = credits;
+            Ok(episode)
+        })
+    }
+
+    pub fn get_person_details(&self, person_id: u32) -> Job<TmdbResult<PersonDetails>> {
+        let url = format!("{PERSON_PATH}/{person_id}");
+        let request = self.new_authorized_get(&url);
 
-            serde_json::from_reader(response.into_reader()).unwrap()
+        Job::new("get_person_details", move || {
+            println!("Executing request in get_person_details");
+            request.fetch_json()
         })
     }
 
-    pub fn get_seasons(&self, series_id: u32) {
-        let url = format!("{SERIES_DETAILS_URL}{series_id}/season/&append_to_response=3");
+    // Movies and series the person played in or worked on, one entry per production
+    pub fn get_person_credits(&self, person_id: u32) -> Job<TmdbResult<Vec<PersonCredit>>> {
+        let url = format!("{PERSON_PATH}/{person_id}/combined_credits");
         let request = self.new_authorized_get(&url);
 
-        println!("Executing request in get_season_details");
-        match request.call() {
-            Ok(response) => {
-                let json_response = response.into_string().unwrap();
-                println!("{}", json_response);
+        Job::new("get_person_credits", move || {
+            println!("Executing request in get_person_credits");
+            let mut payload: Value = request.fetch_json()?;
+            let mut credits: Vec<PersonCredit> = Vec::new();
+            let cast = take_array(&mut payload, "cast")?.into_iter().map(|credit| (credit, "character"));
+            let crew = take_array(&mut payload, "crew")?.into_iter().map(|credit| (credit, "job"));
+
+            for (credit, role_key) in cast.chain(crew) {
+                let role = credit[role_key].as_str().unwrap_or_default().to_string();
+                let production = match parse_production(credit, None) {
+                    Some(Ok(production)) => production,
+                    Some(Err(err)) => {
+                        eprintln!("Skipping a credit: {err}");
+                        continue;
+                    }
+                    None => continue,
+                };
+
+                match credits.iter_mut().find(|existing| existing.production.is_same(&production)) {
+                    Some(existing) => {
+                        if !role.is_empty() && !existing.roles.contains(&role) {
+                            existing.roles.push(role);
+                        }
+                    }
+                    None => {
+                        let roles = if role.is_empty() { Vec::new() } else { vec![role] };
+                        credits.push(PersonCredit { production, roles });
+                    }
+                }
             }
-            Err(err) => {
-                eprintln!("{}", err)
+            Ok(credits)
+        })
+    }
+
+    pub fn get_movie_release_dates(&self, movie_id: u32) -> Job<TmdbResult<Vec<CountryRelease>>> {
+        let url = format!("{MOVIE_PATH}/{movie_id}/release_dates");
+        let request = self.new_authorized_get(&url);
+
+        Job::with_priority(Priority::Low, "get_movie_release_dates", move || {
+            println!("Executing request in get_movie_release_dates");
+            let dates: MovieReleaseDates = request.fetch_json()?;
+            Ok(dates.into())
+        })
+    }
+
+    pub fn get_series_content_ratings(&self, series_id: u32) -> Job<TmdbResult<Vec<CountryRelease>>> {
+        let url = format!("{SERIES_PATH}/{series_id}/content_ratings");
+        let request = self.new_authorized_get(&url);
+
+        Job::with_priority(Priority::Low, "get_series_content_ratings", move || {
+            println!("Executing request in get_series_content_ratings");
+            let ratings: ContentRatings = request.fetch_json()?;
+            Ok(ratings.into())
+        })
+    }
+
+    pub fn get_collection(&self, collection_id: u32) -> Job<TmdbResult<Collection>> {
+        let url = format!("{COLLECTION_PATH}/{collection_id}");
+        let request = self.new_authorized_get(&url);
+
+        Job::new("get_collection", move || {
+            println!("Executing request in get_collection");
+            let mut payload: Value = request.fetch_json()?;
+            let parts = take_array(&mut payload, "parts")?;
+            let mut collection: Collection = serde_json::from_value(payload)?;
+            for part in parts {
+                match parse_production(part, Some("movie")) {
+                    Some(Ok(Production::Movie(movie))) => collection.parts.push(movie),
+                    Some(Err(err)) => eprintln!("Skipping a part of the collection: {err}"),
+                    _ => {}
+                }
             }
-        }
+            collection.sort_parts();
+            Ok(collection)
+        })
     }
 
-    pub fn get_movie_details(&self, movie_id: u32) -> Job<MovieDetails> {
-        let url = format!("{MOVIE_DETAILS_URL}{movie_id}");
+    // Artwork in every language, filtering happens in the artwork browser
+    pub fn get_images(&self, is_tv: bool, id: u32) -> Job<TmdbResult<Images>> {
+        let path = if is_tv { SERIES_PATH } else { MOVIE_PATH };
+        let url = format!("{path}/{id}/images");
+        let request = self
+            .new_authorized_get(&url)
+            .query("include_image_language", &self.media_languages(true));
+
+        Job::new("get_images", move || {
+            println!("Executing request in get_images");
+            request.fetch_json()
+        })
+    }
+
+    // Offers in the configured region, empty if the production isn't available there
+    pub fn get_watch_providers(&self, is_tv: bool, id: u32) -> Job<TmdbResult<WatchProviders>> {
+        let path = if is_tv { SERIES_PATH } else { MOVIE_PATH };
+        let url = format!("{path}/{id}/watch/providers");
         let request = self.new_authorized_get(&url);
+        let region = self.region.to_uppercase();
 
-        Job::new(move || {
-            println!("Executing request in get_movie_details");
-            let Ok(response) = request.call() else {
-                panic!("Error on sending request");
-            };
+        Job::new("get_watch_providers", move || {
+            println!("Executing request in get_watch_providers");
+            let mut payload: Value = request.fetch_json()?;
+            match payload["results"][region.as_str()].take() {
+                Value::Null => Ok(WatchProviders::default()),
+                providers => Ok(serde_json::from_value(providers)?),
+            }
+        })
+    }
 
-            let json_response = response.into_string().unwrap();
-            let mut payload: Value = serde_json::from_str(json_response.as_str()).unwrap();
-            let mut taken_genres = payload["genres"].take();
-            let genres_arr = taken_genres.as_array_mut().unwrap();
-            let mut details: MovieDetails = serde_json::from_value(payload).unwrap();
-            let mut genres: Vec<String> = Vec::with_capacity(genres_arr.len());
-            for genre_obj in genres_arr {
-                genres.push(genre_obj["name"].take().to_string());
+    // Every movie and TV provider operating in the configured region, most prominent first
+    pub fn get_region_providers(&self) -> Job<TmdbResult<Vec<WatchProvider>>> {
+        let query = format!("?watch_region={}", encode_component(&self.region.to_uppercase()));
+        let movie_request = self.new_authorized_get(&format!("{PROVIDERS_MOVIE_PATH}{query}"));
+        let tv_request = self.new_authorized_get(&format!("{PROVIDERS_TV_PATH}{query}"));
+
+        Job::new("get_region_providers", move || {
+            println!("Executing request in get_region_providers");
+            let mut providers: Vec<WatchProvider> = Vec::new();
+            for request in [&movie_request, &tv_request] {
+                let mut payload: Value = request.fetch_json()?;
+                for provider in take_array(&mut payload, "results")? {
+                    let provider: WatchProvider = serde_json::from_value(provider)?;
+                    if !providers.iter().any(|added| added.provider_id == provider.provider_id) {
+                        providers.push(provider);
+                    }
+                }
             }
-            details.genres = genres;
-            details
+            providers.sort_by_key(|provider| provider.display_priority);
+            Ok(providers)
         })
     }
 
-    fn get_imdb_url(&self, endpoint_url: String, prod_name: &str) -> String {
-        let request = self.new_authorized_get(&endpoint_url);
-        let Ok(response) = request.call() else {
-            eprintln!("Error on sending request");
-            return format!("{IMDB_FIND}{prod_name}");
-        };
-        let mut json: Value = serde_json::from_reader(response.into_reader()).unwrap();
-        println!("{}", json);
-        let ids: ProductionIds = serde_json::from_value(json.take()).unwrap();
-        match ids.imdb_id {
-            Some(imdb_id) => format!("{IMDB_TITLE}{imdb_id}"),
-            None => format!("{IMDB_FIND}{prod_name}"),
-        }
+    // Episodes of several seasons, appended to series requests 20 at a time
+    pub fn get_seasons(&self, series_id: u32, season_numbers: &[u32]) -> Job<TmdbResult<Vec<SeasonDetails>>> {
+        let url = format!("{SERIES_PATH}/{series_id}");
+        let requests: Vec<(TmdbRequest, Vec<String>)> = season_numbers
+            .chunks(MAX_APPENDS)
+            .map(|chunk| {
+                let keys: Vec<String> = chunk.iter().map(|number| format!("season/{number}")).collect();
+                let request = self.new_authorized_get(&url).query("append_to_response", &keys.join(","));
+                (request, keys)
+            })
+            .collect();
+
+        Job::new("get_seasons", move || {
+            println!("Executing request in get_seasons");
+            let mut seasons = Vec::new();
+            for (done, (request, keys)) in requests.iter().enumerate() {
+                if jobs::is_cancelled() {
+                    break;
+                }
+                jobs::set_progress(done, requests.len());
+                let mut payload: Value = request.fetch_json()?;
+                for key in keys {
+                    // Seasons that don't exist are left out of the response
+                    match payload[key].take() {
+                        Value::Null => eprintln!("Missing {key} of series {series_id}"),
+                        season => seasons.push(serde_json::from_value(season)?),
+                    }
+                }
+            }
+            Ok(seasons)
+        })
     }
 
-    pub fn get_imdb_url_movie(&self, title: &str, movie_id: u32) -> String {
-        let url = format!("{MOVIE_DETAILS_URL}/{movie_id}/external_ids");
-        self.get_imdb_url(url, title)
+    fn movie_full_details_request(&self, movie_id: u32) -> TmdbRequest {
+        let url = format!("{MOVIE_PATH}/{movie_id}");
+        self.new_authorized_get(&url)
+            .query("append_to_response", MOVIE_APPENDS)
+            .query("include_image_language", &self.media_languages(true))
+            .query("include_video_language", &self.media_languages(false))
+    }
+
+    fn series_full_details_request(&self, series_id: u32) -> TmdbRequest {
+        let url = format!("{SERIES_PATH}/{series_id}");
+        self.new_authorized_get(&url)
+            .query("append_to_response", SERIES_APPENDS)
+            .query("include_image_language", &self.media_languages(true))
+            .query("include_video_language", &self.media_languages(false))
+    }
+
+    pub fn get_movie_full_details(&self, movie_id: u32) -> Job<TmdbResult<MovieFullDetails>> {
+        let request = self.movie_full_details_request(movie_id);
+
+        Job::new("get_movie_full_details", move || {
+            println!("Executing request in get_movie_full_details");
+            parse_movie_full_details(request.fetch_json()?)
+        })
     }
-    pub fn get_imdb_url_series(&self, name: &str, series_id: u32) -> String {
-        let url = format!("{SERIES_DETAILS_URL}/{series_id}/external_ids");
-        self.get_imdb_url(url, name)
+
+    pub fn get_series_full_details(&self, series_id: u32) -> Job<TmdbResult<SeriesFullDetails>> {
+        let request = self.series_full_details_request(series_id);
+
+        Job::new("get_series_full_details", move || {
+            println!("Executing request in get_series_full_details");
+            parse_series_full_details(request.fetch_json()?)
+        })
     }
 
-    pub fn get_movie_trailers(&self, movie_id: u32) -> Vec<Trailer> {
-        self.get_trailers(format!("https://api.themoviedb.org/3/movie/{movie_id}/videos"))
+    fn find_request(&self, external_id: &str, source: ExternalSource) -> TmdbRequest {
+        let url = format!("{FIND_PATH}/{}", encode_component(external_id));
+        self.new_authorized_get(&url).query("external_source", source.param())
     }
 
-    pub fn get_series_trailers(&self, series_id: u32) -> Vec<Trailer> {
-        self.get_trailers(format!("https://api.themoviedb.org/3/tv/{series_id}/videos"))
+    // The movie or series another site's id belongs to, None when TMDB doesn't know it
+    pub fn find_by_external_id(&self, external_id: &str, source: ExternalSource) -> Job<TmdbResult<Option<Production>>> {
+        let request = self.find_request(external_id, source);
+
+        Job::new("find_by_external_id", move || {
+            println!("Executing request in find_by_external_id");
+            parse_find_results(request.fetch_json()?)
+        })
     }
 
-    fn get_trailers(&self, url: String) -> Vec<Trailer> {
-        let request = self.new_authorized_get(&url);
-        let Ok(response) = request.call() else {
-            eprintln!("Error on sending request");
-            return Vec::new();
-        };
-        let mut json: Value = serde_json::from_reader(response.into_reader()).unwrap();
-        let mut results_arr = json["results"].take();
-        let videos = results_arr.as_array_mut().unwrap();
-        let mut trailers: Vec<Trailer> = Vec::new();
-        for vid in videos {
-            if vid["type"] == "Trailer" {
-                let trailer = serde_json::from_value(vid.take()).unwrap();
-                trailers.push(trailer);
+    // Resolves a pasted link to the title it points at
+    pub fn find_by_link(&self, link: ExternalLink) -> Job<TmdbResult<Option<Production>>> {
+        match link {
+            ExternalLink::External(source, external_id) => self.find_by_external_id(&external_id, source),
+            ExternalLink::Tmdb { is_tv, id } => {
+                let path = if is_tv { SERIES_PATH } else { MOVIE_PATH };
+                let request = self.new_authorized_get(&format!("{path}/{id}"));
+
+                Job::new("find_by_link", move || {
+                    println!("Executing request in find_by_link");
+                    let payload = request.fetch_json()?;
+                    let production = if is_tv {
+                        Production::SearchedSeries(serde_json::from_value(payload)?)
+                    } else {
+                        Production::Movie(parse_movie(payload)?)
+                    };
+                    Ok(Some(production))
+                })
+            }
+            // The year is dropped when nothing matches, it may have been part of the title (blade-runner-2049)
+            ExternalLink::Letterboxd { title, year } => {
+                let params = SearchParams::new(title);
+                let with_year = self.search_get(SEARCH_MOVIE_PATH, &params.clone().year(year));
+                let without_year = year.map(|_| self.search_get(SEARCH_MOVIE_PATH, &params));
+
+                Job::new("find_by_link", move || {
+                    println!("Executing request in find_by_link");
+                    for request in std::iter::once(&with_year).chain(&without_year) {
+                        let page = parse_production_page(request.fetch_json()?, Some("movie"))?;
+                        if let Some(production) = page.productions.into_iter().next() {
+                            return Ok(Some(production));
+                        }
+                    }
+                    Ok(None)
+                })
             }
         }
-        trailers
     }
 
-    fn get_keywords(&self, url: String, array_key: &str) -> Vec<Keyword> {
-        let request = self.new_authorized_get(&url);
-        let Ok(response) = request.call() else {
-            eprintln!("Error on sending request");
-            return Vec::new();
-        };
-
-        let mut json: Value = serde_json::from_reader(response.into_reader()).unwrap();
-        let mut keywords_arr = json[array_key].take();
-        let json_keywords = keywords_arr.as_array_mut().unwrap();
-        let mut keywords: Vec<Keyword> = Vec::with_capacity(json_keywords.len());
-        for keyword in json_keywords {
-            keywords.push(serde_json::from_value(keyword.take()).unwrap());
+    // Ids of everything TMDB changed since the given unix time, None when that's too long ago to tell
+    fn get_changed_ids(&self, is_tv: bool, since: u64) -> TmdbResult<Option<HashSet<u32>>> {
+        let now = unix_now();
+        if now.saturating_sub(since) > MAX_CHANGES_AGE_SECS {
+            return Ok(None);
+        }
+        let path = if is_tv { TV_CHANGES_PATH } else { MOVIE_CHANGES_PATH };
+        let start_date = iso_date(since);
+        let mut ids = HashSet::new();
+        let mut page = 1;
+        loop {
+            let mut payload: Value = self
+                .new_authorized_get(path)
+                .query("start_date", &start_date)
+                .query("page", &page.to_string())
+                .fresh()
+                .fetch_json()?;
+            for change in take_array(&mut payload, "results")? {
+                if let Some(id) = change["id"].as_u64() {
+                    ids.insert(id as u32);
+                }
+            }
+            let total_pages = payload["total_pages"].as_u64().unwrap_or(1) as u32;
+            if page >= total_pages {
+                return Ok(Some(ids));
+            }
+            if page >= MAX_CHANGES_PAGES {
+                return Ok(None);
+            }
+            page += 1;
         }
-        keywords
     }
 
-    pub fn get_keywords_movie(&self, movie_id: u32) -> Vec<Keyword> {
-        let url = format!("{MOVIE_DETAILS_URL}/{movie_id}/keywords");
-        self.get_keywords(url, "keywords")
+    fn get_fresh_movie(&self, movie_id: u32) -> TmdbResult<Movie> {
+        let url = format!("{MOVIE_PATH}/{movie_id}");
+        parse_movie(self.new_authorized_get(&url).fresh().fetch_json()?)
     }
-    pub fn get_keywords_series(&self, series_id: u32) -> Vec<Keyword> {
-        let url = format!("{SERIES_DETAILS_URL}/{series_id}/keywords");
-        self.get_keywords(url, "results")
+
+    fn get_fresh_series(&self, series_id: u32) -> TmdbResult<Series> {
+        let url = format!("{SERIES_PATH}/{series_id}");
+        parse_series(self.new_authorized_get(&url).fresh().fetch_json()?)
     }
 
-    pub fn download_poster(&self, poster_url: &str, file_path: &str) {
-        let agent = self.agent.clone();
-        let mut file = std::fs::File::create(file_path).expect("looks like someone tried to unwrap...");
-        let request = agent.get(poster_url);
+    // Entries are (id, title). With refreshed_at set, entries TMDB hasn't changed since then are skipped.
+    pub fn refresh_library(
+        &self,
+        movies: Vec<(u32, String)>,
+        series: Vec<(u32, String)>,
+        refreshed_at: Option<u64>,
+    ) -> Job<RefreshOutcome> {
+        self.refresh(movies, series, refreshed_at, true)
+    }
 
-        std::thread::spawn(move || {
-            println!("Executing request in download_poster");
-            let Ok(response) = request.call() else {
-                panic!("Error on sending request");
+    pub fn refresh_entry(&self, is_tv: bool, id: u32, title: String) -> Job<RefreshOutcome> {
+        let entry = vec![(id, title)];
+        if is_tv {
+            self.refresh(Vec::new(), entry, None, false)
+        } else {
+            self.refresh(entry, Vec::new(), None, false)
+        }
+    }
+
+    fn refresh(
+        &self,
+        movies: Vec<(u32, String)>,
+        series: Vec<(u32, String)>,
+        refreshed_at: Option<u64>,
+        whole_library: bool,
+    ) -> Job<RefreshOutcome> {
+        let movie_db = self.clone();
+
+        Job::with_priority(Priority::Low, "refresh", move || {
+            println!("Executing requests in refresh");
+            let mut outcome = RefreshOutcome {
+                refreshed: Vec::new(),
+                failed: Vec::new(),
+                skipped: 0,
+                whole_library,
+                started_at: unix_now(),
+            };
+            // A failed changes lookup only costs the shortcut
+            let changed_ids = |is_tv: bool| {
+                let since = refreshed_at?;
+                movie_db.get_changed_ids(is_tv, since).unwrap_or_else(|err| {
+                    eprintln!("Couldn't look up changes: {err}");
+                    None
+                })
             };
-            let bytes_written = std::io::copy(&mut response.into_reader(), &mut file).unwrap();
+
+            let total = movies.len() + series.len();
+            let changed_movies = changed_ids(false);
+            for (done, (id, title)) in movies.iter().enumerate() {
+                // Whatever was refreshed so far is still worth applying
+                if jobs::is_cancelled() {
+                    return outcome;
+                }
+                jobs::set_progress(done, total);
+                if changed_movies.as_ref().is_some_and(|changed| !changed.contains(id)) {
+                    outcome.skipped += 1;
+                    continue;
+                }
+                match movie_db.get_fresh_movie(*id) {
+                    Ok(movie) => outcome.refreshed.push(Refreshed::Movie(movie)),
+                    Err(err) => outcome.failed.push((title.clone(), err)),
+                }
+            }
+
+            let changed_series = changed_ids(true);
+            for (done, (id, title)) in series.iter().enumerate() {
+                if jobs::is_cancelled() {
+                    return outcome;
+                }
+                jobs::set_progress(movies.len() + done, total);
+                if changed_series.as_ref().is_some_and(|changed| !changed.contains(id)) {
+                    outcome.skipped += 1;
+                    continue;
+                }
+                match movie_db.get_fresh_series(*id) {
+                    Ok(series) => outcome.refreshed.push(Refreshed::Series(series)),
+                    Err(err) => outcome.failed.push((title.clone(), err)),
+                }
+            }
+            outcome
+        })
+    }
+
+    // Returns the number of bytes written to file_path
+    pub fn download_poster(&self, poster_url: &str, file_path: &str) -> Job<TmdbResult<u64>> {
+        let request = TmdbRequest {
+            request: self.agent.get(poster_url),
+            fallback: None,
+            cache: None,
+            fresh: false,
+            limiter: self.limiter.clone(),
+        };
+        let file_path = file_path.to_string();
+
+        Job::with_priority(Priority::Low, "download_poster", move || {
+            println!("Executing request in download_poster");
+            let response = request.call_throttled()?;
+            let mut file = std::fs::File::create(&file_path)?;
+            let bytes_written = std::io::copy(&mut response.into_reader(), &mut file)?;
             println!("bytes written {}", bytes_written);
-        });
+            Ok(bytes_written)
+        })
     }
 }
 
-#[allow(dead_code)]
-struct VecMap<K, V> {
-    keys_to_values: Vec<(K, V)>,
-}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock_tmdb;
+    use std::net::TcpListener;
+    use std::path::PathBuf;
 
-#[allow(dead_code)]
-impl<K: PartialEq, V> VecMap<K, V> {
-    pub fn new() -> VecMap<K, V> {
-        Self { keys_to_values: vec![] }
+    fn language_pairs(request: &ureq::Request) -> Vec<String> {
+        let url = request.url();
+        let query = url.split_once('?').map_or("", |(_, query)| query);
+        query.split('&').filter(|pair| pair.starts_with("language=")).map(str::to_string).collect()
     }
 
-    pub fn put_value(&mut self, key: K, value: V) {
-        self.keys_to_values.push((key, value));
+    #[test]
+    fn search_language_replaces_the_configured_one() {
+        let mut movie_db = TheMovieDB::new(&Config::default());
+        movie_db.language = "de-DE".into();
+        movie_db.fallback_language = "en-US".into();
+
+        let request = movie_db.search_get(SEARCH_MULTI_PATH, &SearchParams::new("dune").language("pl-PL"));
+        assert_eq!(language_pairs(&request.request), ["language=pl-PL"]);
+        assert_eq!(language_pairs(request.fallback.as_ref().unwrap()), ["language=en-US"]);
+
+        let request = movie_db.search_get(SEARCH_MULTI_PATH, &SearchParams::new("dune"));
+        assert_eq!(language_pairs(&request.request), ["language=de-DE"]);
     }
 
-    // pub fn put_shared(&mut self, key: K, value: Arc<V>){
-    //     self.keys_to_values.push((key, value));
-    // }
+    // A client talking to the mock server over res/fixtures, started on a free port for every test
+    fn mock_movie_db() -> TheMovieDB {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let fixtures = Arc::new(PathBuf::from(concat!(env!("CARGO_MANIFEST_DIR"), "/res/fixtures")));
+        std::thread::spawn(move || mock_tmdb::serve(listener, fixtures));
 
-    pub fn get(&self, key: &K) -> Option<&V> {
-        self.keys_to_values.iter().find(|(k, _)| k.eq(key)).map(|(_, v)| v)
+        let config = Config {
+            access_token: "token".into(),
+            enable_cache: false,
+            api_url: format!("http://{address}/3"),
+            image_url: format!("http://{address}/t/p/"),
+            ..Config::default()
+        };
+        TheMovieDB::new(&config)
     }
-    pub fn remove(&mut self, key: K) {
-        let mut index: i64 = -1;
-        for (i, pair) in self.keys_to_values.iter().enumerate() {
-            if pair.0 == key {
-                index = i as i64;
-                break;
-            }
-        }
-        if index != -1 {
-            self.keys_to_values.remove(index as usize);
-        }
+
+    fn wait<T: JobOutput>(mut job: Job<T>) -> T {
+        job.poll_blocking().expect("the job was started")
     }
-    pub fn size(&self) -> usize {
-        self.keys_to_values.len()
+
+    #[test]
+    fn searches_parse() {
+        let movie_db = mock_movie_db();
+        let page = wait(movie_db.search_production(&SearchParams::new("fight"), None)).unwrap();
+        assert_eq!(page.productions.len(), 3);
+        assert!(matches!(page.productions[0], Production::Movie(_)));
+        assert!(matches!(page.productions[1], Production::SearchedSeries(_)));
+        assert!(matches!(page.productions[2], Production::Person(_)));
+
+        let movies = wait(movie_db.search_movie(&SearchParams::new("fight"))).unwrap();
+        assert!(movies.productions.iter().all(|prod| matches!(prod, Production::Movie(_))));
+        let series = wait(movie_db.search_tv(&SearchParams::new("thrones"))).unwrap();
+        assert!(series.productions.iter().all(|prod| matches!(prod, Production::SearchedSeries(_))));
+    }
+
+    #[test]
+    fn year_search_pages_movies_and_series_separately() {
+        let movie_db = mock_movie_db();
+        let params = SearchParams::new("fight").year(Some(1999));
+        let first = wait(movie_db.search_production(&params, None)).unwrap();
+        let sources = first.sources.expect("a merged page");
+        assert_eq!(first.total_results, sources.movies.total_results + sources.series.total_results);
+        assert!(first.productions.iter().any(|prod| matches!(prod, Production::Movie(_))));
+
+        // Movies ran out after the first page, only series are requested for the second
+        let sources = PagedSources {
+            movies: sources.movies,
+            series: PageTotals { total_pages: 2, ..sources.series },
+        };
+        let second = wait(movie_db.search_production(&params.clone().page(2), Some(sources))).unwrap();
+        assert!(!second.productions.is_empty());
+        assert!(second.productions.iter().all(|prod| matches!(prod, Production::SearchedSeries(_))));
+        assert_eq!(second.sources.unwrap().movies, sources.movies);
+    }
+
+    #[test]
+    fn movie_details_parse_with_trailers_and_keywords() {
+        let movie_db = mock_movie_db();
+        let details = wait(movie_db.get_movie_full_details(550)).unwrap();
+        assert_eq!(details.external_ids.imdb_id.as_deref(), Some("tt0137523"));
+        assert!(!details.trailers.is_empty());
+        assert!(!details.keywords.is_empty());
+        assert!(!details.credits.cast.is_empty());
+    }
+
+    #[test]
+    fn series_details_and_seasons_parse() {
+        let movie_db = mock_movie_db();
+        let details = wait(movie_db.get_series_full_details(1399)).unwrap();
+        assert!(details.details.number_of_seasons > 0);
+        assert!(!details.trailers.is_empty());
+        assert!(!details.keywords.is_empty());
+
+        let season = wait(movie_db.get_season_details(1399, 1)).unwrap();
+        assert_eq!(season.season_number, 1);
+        assert!(!season.episodes.is_empty());
+
+        let episode = wait(movie_db.get_episode_details(1399, 1, 1)).unwrap();
+        assert_eq!((episode.season_number, episode.episode_number), (1, 1));
+    }
+
+    #[test]
+    fn missing_fixtures_are_status_errors() {
+        let movie_db = mock_movie_db();
+        assert!(matches!(wait(movie_db.get_movie_full_details(1)), Err(TmdbError::Status(404, _))));
     }
 }
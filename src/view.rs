@@ -1,30 +1,140 @@
 use crate::{
-    jobs::Job,
-    movies::Movie,
-    production::Trailer,
-    series::{SeasonDetails, SeriesDetails},
-    themoviedb::{TheMovieDB, Width},
+    credits::Credits,
+    jobs::{self, Job, TaskStatus},
+    movies::{Collection, Movie, MovieFullDetails},
+    releases::CountryRelease,
+    person::{sort_credits, CreditSort, PersonCredit, PersonDetails},
+    production::{Artwork, Images, Keyword, LibraryIds, Production, ProductionIds, ProductionPage, Trailer, WatchProviders},
+    series::{EpisodeDetails, SeasonDetails, SeriesFullDetails},
+    images::ImageKind,
+    themoviedb::{TheMovieDB, TmdbResult},
 };
 
-use crate::movies::MovieDetails;
 use crate::series::SearchedSeries;
-use egui::{include_image, Id, ImageSource::Uri, Label, Sense};
+use crate::app::error_toast;
+use egui::{include_image, Id, ImageSource::Uri, Label, Sense, Vec2};
+use egui_toast::Toasts;
 
 pub struct SeriesView {
     window_open: bool,
     window_title: String,
     series: Option<SearchedSeries>,
 
-    series_details: Job<SeriesDetails>,
-    season_details: Job<SeasonDetails>,
+    full_details: Job<TmdbResult<SeriesFullDetails>>,
+    season_details: Job<TmdbResult<SeasonDetails>>,
     expanded_season: bool,
+    providers: Job<TmdbResult<WatchProviders>>,
+    recommendations: Job<TmdbResult<ProductionPage>>,
+    similar: Job<TmdbResult<ProductionPage>>,
 }
 
 pub struct MovieView {
     window_open: bool,
     window_title: String,
     movie: Option<Movie>,
-    movie_details: Job<MovieDetails>,
+    // Backdrop picked in the artwork browser, the default one from the details is used otherwise
+    header: Option<String>,
+    full_details: Job<TmdbResult<MovieFullDetails>>,
+    providers: Job<TmdbResult<WatchProviders>>,
+    recommendations: Job<TmdbResult<ProductionPage>>,
+    similar: Job<TmdbResult<ProductionPage>>,
+}
+
+pub struct PersonView {
+    window_open: bool,
+    person_id: Option<u32>,
+    name: String,
+    details: Job<TmdbResult<PersonDetails>>,
+    credits: Job<TmdbResult<Vec<PersonCredit>>>,
+    // Filled in once credits arrive so it can be re-sorted in place
+    filmography: Option<Vec<PersonCredit>>,
+    sort: CreditSort,
+}
+
+// Things a view asks the app to do, they need access to the library
+pub enum ViewAction {
+    OpenPerson(u32, String),
+    AddProduction(Production),
+    // is_tv, id, title, images that came with the details
+    OpenArtwork(bool, u32, String, Images),
+    // is_tv, id, None goes back to the default poster
+    SetPoster(bool, u32, Option<String>),
+    // Movie id, None goes back to the default backdrop
+    SetBackdrop(u32, Option<String>),
+    // Collection id, name
+    OpenCollection(u32, String),
+    // Every movie missing from the library, in release order
+    AddMovies(Vec<Movie>),
+    // Title, trailers
+    ShowTrailers(String, Vec<Trailer>),
+    // Series id, series name, season and episode number
+    OpenEpisode(u32, String, u32, u32),
+    // Series id, season and episode number, note
+    SetEpisodeNote(u32, u32, u32, String),
+}
+
+pub struct EpisodeView {
+    window_open: bool,
+    // Series id, season and episode number
+    target: Option<(u32, u32, u32)>,
+    title: String,
+    details: Job<TmdbResult<EpisodeDetails>>,
+    // The user's note, None when there's nowhere to keep it (not in the library or a special)
+    note: Option<String>,
+}
+
+pub struct CollectionView {
+    window_open: bool,
+    collection_id: Option<u32>,
+    name: String,
+    collection: Job<TmdbResult<Collection>>,
+}
+
+#[derive(Copy, Clone, PartialEq)]
+enum ArtworkKind {
+    Posters,
+    Backdrops,
+    Logos,
+}
+
+impl ArtworkKind {
+    const ALL: [ArtworkKind; 3] = [ArtworkKind::Posters, ArtworkKind::Backdrops, ArtworkKind::Logos];
+
+    fn label(&self) -> &'static str {
+        match self {
+            ArtworkKind::Posters => "Posters",
+            ArtworkKind::Backdrops => "Backdrops",
+            ArtworkKind::Logos => "Logos",
+        }
+    }
+
+    fn image_kind(&self) -> ImageKind {
+        match self {
+            ArtworkKind::Posters => ImageKind::Poster,
+            ArtworkKind::Backdrops => ImageKind::Backdrop,
+            ArtworkKind::Logos => ImageKind::Logo,
+        }
+    }
+
+    // Thumbnail size
+    fn size(&self) -> Vec2 {
+        match self {
+            ArtworkKind::Posters => Vec2::new(100.0, 150.0),
+            ArtworkKind::Backdrops => Vec2::new(200.0, 112.0),
+            ArtworkKind::Logos => Vec2::new(150.0, 60.0),
+        }
+    }
+}
+
+pub struct ArtworkView {
+    window_open: bool,
+    // is_tv, id
+    target: Option<(bool, u32)>,
+    title: String,
+    images: Job<TmdbResult<Images>>,
+    kind: ArtworkKind,
+    // Outer None shows every language, Some(None) only images without text
+    language: Option<Option<String>>,
 }
 
 pub struct TrailersView {
@@ -33,6 +143,18 @@ pub struct TrailersView {
     trailers: Vec<Trailer>,
 }
 
+// Queued and running jobs, failed ones until they're retried or dismissed
+pub struct TasksView {
+    pub is_open: bool,
+}
+
+// What the last metadata refresh changed, one entry per title
+pub struct RefreshReportView {
+    is_open: bool,
+    changes: Vec<(String, Vec<String>)>,
+    failures: Vec<String>,
+}
+
 pub struct LicenseView {
     pub is_open: bool,
     title: String,
@@ -45,9 +167,12 @@ impl SeriesView {
             window_open: false,
             window_title: "".into(),
             series: None,
-            series_details: Job::Empty,
+            full_details: Job::Empty,
             season_details: Job::Empty,
             expanded_season: false,
+            providers: Job::Empty,
+            recommendations: Job::Empty,
+            similar: Job::Empty,
         }
     }
 
@@ -56,17 +181,35 @@ impl SeriesView {
         self.window_title = series.name.clone();
         self.series = Some(series);
 
-        self.series_details = movie_db.get_series_details(id);
+        self.full_details = movie_db.get_series_full_details(id);
         self.season_details = Job::Empty;
+        self.providers = movie_db.get_watch_providers(true, id);
+        self.recommendations = movie_db.get_recommendations(true, id, 1);
+        self.similar = movie_db.get_similar(true, id, 1);
         self.window_open = true;
         self.expanded_season = false;
     }
 
-    pub fn draw(&mut self, ctx: &egui::Context, movie_db: &TheMovieDB) {
-        let Some(series) = self.series.as_ref() else { return };
-        let Some(series_details) = self.series_details.poll() else {
-            return;
+    pub fn draw(
+        &mut self,
+        ctx: &egui::Context,
+        movie_db: &TheMovieDB,
+        library: &LibraryIds,
+        toasts: &mut Toasts,
+    ) -> Option<ViewAction> {
+        let series = self.series.as_ref()?;
+        let full_details = match self.full_details.poll() {
+            None => return None,
+            Some(Ok(details)) => details,
+            Some(Err(err)) => {
+                toasts.add(error_toast(format!("Failed to fetch {}: {err}", series.name)));
+                self.full_details = Job::Empty;
+                self.series = None;
+                return None;
+            }
         };
+        let series_details = &full_details.details;
+        let mut action = None;
 
         let seasons_per_row = std::cmp::min(5, series_details.seasons.len());
 
@@ -79,8 +222,16 @@ impl SeriesView {
 
         window.show(ctx, |ui| {
             if self.expanded_season {
-                let Some(season_details) = self.season_details.poll() else {
-                    return;
+                let season_details = match self.season_details.poll() {
+                    None => return,
+                    Some(Ok(details)) => details,
+                    Some(Err(err)) => {
+                        toasts.add(error_toast(format!("Failed to fetch the season: {err}")));
+                        self.season_details = Job::Empty;
+                        self.expanded_season = false;
+                        self.window_title = series.name.clone();
+                        return;
+                    }
                 };
 
                 if ui.button("<=").clicked() {
@@ -95,7 +246,17 @@ impl SeriesView {
                     ui.vertical(|ui| {
                         egui::ScrollArea::vertical().show(ui, |ui| {
                             for episode in &season_details.episodes {
-                                ui.label(format!("{}# {}", episode.episode_number, episode.name));
+                                let label = format!("{}# {}", episode.episode_number, episode.name);
+                                let response = ui.add(Label::new(label).sense(Sense::click()));
+                                let response = match &episode.air_date {
+                                    Some(air_date) => response.on_hover_text(format!("Aired {air_date}")),
+                                    None => response,
+                                };
+                                if response.clicked() {
+                                    let season_number = season_details.season_number;
+                                    let episode_number = episode.episode_number;
+                                    action = Some(ViewAction::OpenEpisode(series.id, series.name.clone(), season_number, episode_number));
+                                }
                             }
                         });
                     });
@@ -103,10 +264,32 @@ impl SeriesView {
                 return;
             }
 
+            if !series.original_name.is_empty() && series.original_name != series.name {
+                ui.label(format!("Original name: {}", series.original_name));
+            }
             ui.label(&series.overview);
             ui.label(format!("Seasons: {}", series_details.number_of_seasons));
             ui.label(format!("Episodes: {}", series_details.number_of_episodes));
             ui.label(format!("Status: {}", series_details.status));
+            if !series_details.genres.is_empty() {
+                let genres: Vec<&str> = series_details.genres.iter().map(|genre| genre.name.as_str()).collect();
+                ui.label(format!("Genres: {}", genres.join(", ")));
+            }
+            ui.horizontal(|ui| {
+                if ui.button("Browse artwork").clicked() {
+                    let images = full_details.images.clone();
+                    action = Some(ViewAction::OpenArtwork(true, series.id, series.name.clone(), images));
+                }
+                action = draw_links(ui, &series.name, &full_details.external_ids, &full_details.trailers).or(action.take());
+            });
+            ui.separator();
+
+            draw_watch_providers(ui, "series_providers", &mut self.providers, movie_db);
+            draw_releases(ui, "series_ratings", "Content ratings", &full_details.ratings, movie_db);
+            action = draw_credits(ui, "series_cast", &full_details.credits, movie_db).or(action.take());
+            draw_keywords(ui, "series_keywords", &full_details.keywords);
+            draw_related(ui, "Recommended", &mut self.recommendations, movie_db, library);
+            draw_related(ui, "Similar", &mut self.similar, movie_db, library);
             ui.separator();
 
             if series_details.seasons.len() <= 5 {
@@ -120,7 +303,7 @@ impl SeriesView {
                             // it's a bad idea to fetch posters for every season
                             let image = match season.poster_path.as_ref() {
                                 Some(url) => {
-                                    let image_url = TheMovieDB::get_full_poster_url(url, Width::W300);
+                                    let image_url = movie_db.get_image_url(url, ImageKind::Poster, 100.0);
                                     egui::Image::new(Uri(image_url.into())).sense(Sense::click())
                                 }
                                 None => egui::Image::new(include_image!("../res/no_image.png")).sense(Sense::click()),
@@ -147,6 +330,7 @@ impl SeriesView {
                 });
             });
         });
+        action
     }
 }
 
@@ -156,7 +340,18 @@ impl MovieView {
             window_open: false,
             window_title: "".to_string(),
             movie: None,
-            movie_details: Job::Empty,
+            header: None,
+            full_details: Job::Empty,
+            providers: Job::Empty,
+            recommendations: Job::Empty,
+            similar: Job::Empty,
+        }
+    }
+
+    // Only applies if the movie is the one being shown
+    pub fn set_header(&mut self, movie_id: u32, backdrop: Option<String>) {
+        if self.movie.as_ref().is_some_and(|movie| movie.id == movie_id) {
+            self.header = backdrop;
         }
     }
 
@@ -164,32 +359,68 @@ impl MovieView {
         let id = movie.id;
         self.window_title = movie.title.clone();
         self.movie = Some(movie);
-        self.movie_details = movie_db.get_movie_details(id);
+        self.header = None;
+        self.full_details = movie_db.get_movie_full_details(id);
+        self.providers = movie_db.get_watch_providers(false, id);
+        self.recommendations = movie_db.get_recommendations(false, id, 1);
+        self.similar = movie_db.get_similar(false, id, 1);
         self.window_open = true;
     }
 
-    pub fn draw(&mut self, ctx: &egui::Context, _movie_db: &TheMovieDB) {
-        let Some(ref movie) = self.movie else {
-            return;
-        };
-        let Some(movie_details) = self.movie_details.poll() else {
-            return;
+    pub fn draw(
+        &mut self,
+        ctx: &egui::Context,
+        movie_db: &TheMovieDB,
+        library: &LibraryIds,
+        toasts: &mut Toasts,
+    ) -> Option<ViewAction> {
+        let movie = self.movie.as_ref()?;
+        let full_details = match self.full_details.poll() {
+            None => return None,
+            Some(Ok(details)) => details,
+            Some(Err(err)) => {
+                toasts.add(error_toast(format!("Failed to fetch {}: {err}", movie.title)));
+                self.full_details = Job::Empty;
+                self.movie = None;
+                return None;
+            }
         };
+        let movie_details = &full_details.details;
+        let mut action = None;
 
         let window = egui::Window::new(&movie.title)
             .open(&mut self.window_open)
             .resizable(true);
 
         window.show(ctx, |ui| {
+            if let Some(backdrop) = self.header.as_ref().or(movie_details.backdrop_path.as_ref()) {
+                let image_url = movie_db.get_image_url(backdrop, ImageKind::Backdrop, ui.available_width());
+                ui.add(egui::Image::new(image_url).max_height(200.0).rounding(4.0));
+            }
+            ui.horizontal(|ui| {
+                if ui.button("Browse artwork").clicked() {
+                    let images = full_details.images.clone();
+                    action = Some(ViewAction::OpenArtwork(false, movie.id, movie.title.clone(), images));
+                }
+                if let Some(collection) = &movie_details.belongs_to_collection {
+                    if ui.button(format!("Part of {}", collection.name)).clicked() {
+                        action = Some(ViewAction::OpenCollection(collection.id, collection.name.clone()));
+                    }
+                }
+                action = draw_links(ui, &movie.title, &full_details.external_ids, &full_details.trailers).or(action.take());
+            });
             ui.label(&movie.overview);
             ui.separator();
             ui.horizontal_wrapped(|ui| {
                 if let Some(ref poster) = movie.poster_path {
-                    let image_url = TheMovieDB::get_full_poster_url(poster, Width::W400);
                     let width = 250.0;
+                    let image_url = movie_db.get_image_url(poster, ImageKind::Poster, width);
                     ui.add_sized([width, width * 1.5], egui::Image::new(image_url));
                 }
                 ui.vertical_centered(|ui| {
+                    if !movie.original_title.is_empty() && movie.original_title != movie.title {
+                        ui.label(format!("Original title: {}", movie.original_title));
+                    }
                     ui.label(format!("Language: {}", movie.original_language.to_uppercase()));
                     ui.label(format!("Released: {}", movie.release_date));
                     ui.label(format!("Runtime: {}min", movie_details.runtime));
@@ -205,8 +436,8 @@ impl MovieView {
                         movie_details.revenue()
                     };
                     ui.label(format!("Revenue: {revenue}"));
-                    let genres = movie_details.genres.join(",");
-                    ui.label(format!("Genres: {}", genres.trim_end_matches(',')));
+                    let genres: Vec<&str> = movie_details.genres.iter().map(|genre| genre.name.as_str()).collect();
+                    ui.label(format!("Genres: {}", genres.join(", ")));
                     let mut companies = String::with_capacity(movie_details.production_companies.len() * 10);
                     for company in &movie_details.production_companies {
                         companies.push_str(&company.name);
@@ -216,7 +447,523 @@ impl MovieView {
                     ui.label(format!("Tagline: {}", movie_details.tagline));
                 });
             });
+            ui.separator();
+
+            draw_watch_providers(ui, "movie_providers", &mut self.providers, movie_db);
+            draw_releases(ui, "movie_releases", "Release dates & ratings", &full_details.releases, movie_db);
+            action = draw_credits(ui, "movie_cast", &full_details.credits, movie_db).or(action.take());
+            draw_keywords(ui, "movie_keywords", &full_details.keywords);
+            draw_related(ui, "Recommended", &mut self.recommendations, movie_db, library);
+            draw_related(ui, "Similar", &mut self.similar, movie_db, library);
+        });
+        action
+    }
+}
+
+impl PersonView {
+    pub fn new() -> Self {
+        Self {
+            window_open: false,
+            person_id: None,
+            name: String::new(),
+            details: Job::Empty,
+            credits: Job::Empty,
+            filmography: None,
+            sort: CreditSort::Newest,
+        }
+    }
+
+    pub fn set_person(&mut self, person_id: u32, name: String, movie_db: &TheMovieDB) {
+        self.person_id = Some(person_id);
+        self.name = name;
+        self.details = movie_db.get_person_details(person_id);
+        self.credits = movie_db.get_person_credits(person_id);
+        self.filmography = None;
+        self.window_open = true;
+    }
+
+    pub fn draw(
+        &mut self,
+        ctx: &egui::Context,
+        movie_db: &TheMovieDB,
+        library: &LibraryIds,
+        toasts: &mut Toasts,
+    ) -> Option<ViewAction> {
+        let person_id = self.person_id?;
+        if let Some(result) = self.credits.poll_owned() {
+            match result {
+                Ok(mut credits) => {
+                    sort_credits(&mut credits, self.sort);
+                    self.filmography = Some(credits);
+                }
+                Err(err) => {
+                    toasts.add(error_toast(format!("Failed to fetch credits of {}: {err}", self.name)));
+                    self.filmography = Some(Vec::new());
+                }
+            }
+        }
+
+        let details = match self.details.poll() {
+            None => return None,
+            Some(Ok(details)) => details,
+            Some(Err(err)) => {
+                toasts.add(error_toast(format!("Failed to fetch {}: {err}", self.name)));
+                self.details = Job::Empty;
+                self.person_id = None;
+                return None;
+            }
+        };
+
+        let mut action = None;
+        let window = egui::Window::new(&self.name)
+            .id(Id::new(("person", person_id)))
+            .open(&mut self.window_open)
+            .default_width(450.0)
+            .resizable(true);
+
+        window.show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                let image = match details.profile_path.as_ref() {
+                    Some(path) => egui::Image::new(movie_db.get_image_url(path, ImageKind::Profile, 100.0)),
+                    None => egui::Image::new(include_image!("../res/no_image.png")),
+                };
+                ui.add_sized([100.0, 150.0], image);
+                ui.vertical(|ui| {
+                    ui.heading(&details.name);
+                    ui.label(format!("Known for: {}", details.known_for_department));
+                    ui.label(details.lifespan());
+                    if let Some(place) = &details.place_of_birth {
+                        ui.label(format!("Place of birth: {place}"));
+                    }
+                });
+            });
+            ui.separator();
+
+            egui::CollapsingHeader::new("Biography").default_open(true).show(ui, |ui| {
+                egui::ScrollArea::vertical().id_source("biography").max_height(150.0).show(ui, |ui| {
+                    if details.biography.is_empty() {
+                        ui.label("No biography available");
+                    } else {
+                        ui.label(&details.biography);
+                    }
+                });
+            });
+            ui.separator();
+
+            ui.horizontal(|ui| {
+                ui.label("Sort by");
+                for sort in CreditSort::ALL {
+                    if ui.selectable_value(&mut self.sort, sort, sort.label()).changed() {
+                        if let Some(filmography) = self.filmography.as_mut() {
+                            sort_credits(filmography, sort);
+                        }
+                    }
+                }
+            });
+
+            let Some(filmography) = self.filmography.as_ref() else {
+                ui.spinner();
+                return;
+            };
+            if filmography.is_empty() {
+                ui.label("No known works");
+                return;
+            }
+
+            egui::ScrollArea::vertical().id_source("filmography").show(ui, |ui| {
+                for credit in filmography {
+                    let work = &credit.production;
+                    let (kind, add_label) = match work {
+                        Production::Movie(_) => ("Movie", "Add movie"),
+                        Production::SearchedSeries(_) => ("Series", "Add series"),
+                        Production::Person(_) => continue,
+                    };
+                    ui.horizontal(|ui| {
+                        let image = match work.poster_path() {
+                            Some(path) => egui::Image::new(movie_db.get_image_url(path, ImageKind::Poster, 40.0)),
+                            None => egui::Image::new(include_image!("../res/no_image.png")),
+                        };
+                        ui.add_sized([40.0, 60.0], image);
+                        ui.vertical(|ui| {
+                            let in_library = library.contains(work);
+                            if in_library {
+                                ui.label(format!("\u{2714} {}", work.title()));
+                            } else {
+                                ui.label(work.title());
+                            }
+                            ui.label(format!("{kind} | {}", work.release_date()));
+                            if !credit.roles.is_empty() {
+                                ui.weak(credit.roles.join(", "));
+                            }
+                            if !in_library && ui.small_button(add_label).clicked() {
+                                action = Some(ViewAction::AddProduction(work.clone()));
+                            }
+                        });
+                    });
+                }
+            });
+        });
+        action
+    }
+}
+
+impl EpisodeView {
+    pub fn new() -> Self {
+        Self {
+            window_open: false,
+            target: None,
+            title: String::new(),
+            details: Job::Empty,
+            note: None,
+        }
+    }
+
+    pub fn set_episode(
+        &mut self,
+        series_id: u32,
+        series_name: &str,
+        season_number: u32,
+        episode_number: u32,
+        note: Option<String>,
+        movie_db: &TheMovieDB,
+    ) {
+        self.target = Some((series_id, season_number, episode_number));
+        self.title = format!("{series_name} S{season_number:02}E{episode_number:02}");
+        self.details = movie_db.get_episode_details(series_id, season_number, episode_number);
+        self.note = note;
+        self.window_open = true;
+    }
+
+    // Keeps the note in step when it's edited somewhere else
+    pub fn update_note(&mut self, series_id: u32, season_number: u32, episode_number: u32, note: &str) {
+        if self.target == Some((series_id, season_number, episode_number)) {
+            self.note = Some(note.to_string());
+        }
+    }
+
+    pub fn draw(
+        &mut self,
+        ctx: &egui::Context,
+        movie_db: &TheMovieDB,
+        _library: &LibraryIds,
+        toasts: &mut Toasts,
+    ) -> Option<ViewAction> {
+        let (series_id, season_number, episode_number) = self.target?;
+        let episode = match self.details.poll() {
+            None => return None,
+            Some(Ok(episode)) => episode,
+            Some(Err(err)) => {
+                toasts.add(error_toast(format!("Failed to fetch {}: {err}", self.title)));
+                self.details = Job::Empty;
+                self.target = None;
+                return None;
+            }
+        };
+        let mut action = None;
+
+        let window = egui::Window::new(&self.title)
+            .id(Id::new(("episode", series_id, season_number, episode_number)))
+            .open(&mut self.window_open)
+            .default_width(400.0)
+            .resizable(true);
+
+        window.show(ctx, |ui| {
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                if let Some(still) = &episode.still_path {
+                    let width = ui.available_width().min(400.0);
+                    let image_url = movie_db.get_image_url(still, ImageKind::Still, width);
+                    ui.add_sized([width, width * 9.0 / 16.0], egui::Image::new(image_url));
+                }
+                ui.heading(&episode.name);
+                ui.horizontal_wrapped(|ui| {
+                    if let Some(air_date) = &episode.air_date {
+                        ui.label(format!("Aired: {air_date}"));
+                    }
+                    if let Some(runtime) = episode.runtime {
+                        ui.label(format!("Runtime: {runtime}min"));
+                    }
+                    ui.label(format!("Rating: {:.1} / 10 ({} votes)", episode.vote_average, episode.vote_count));
+                });
+                ui.label(&episode.overview);
+                ui.separator();
+
+                match &mut self.note {
+                    Some(note) => {
+                        ui.label("Your note");
+                        if ui.text_edit_multiline(note).changed() {
+                            action = Some(ViewAction::SetEpisodeNote(series_id, season_number, episode_number, note.clone()));
+                        }
+                    }
+                    None => {
+                        ui.weak("Notes are kept for numbered seasons of series in your library");
+                    }
+                }
+                ui.separator();
+
+                action = draw_credits(ui, "episode_credits", &episode.credits, movie_db).or(action.take());
+            });
+        });
+        action
+    }
+}
+
+impl CollectionView {
+    pub fn new() -> Self {
+        Self {
+            window_open: false,
+            collection_id: None,
+            name: String::new(),
+            collection: Job::Empty,
+        }
+    }
+
+    pub fn set_collection(&mut self, collection_id: u32, name: String, movie_db: &TheMovieDB) {
+        self.collection_id = Some(collection_id);
+        self.name = name;
+        self.collection = movie_db.get_collection(collection_id);
+        self.window_open = true;
+    }
+
+    pub fn draw(
+        &mut self,
+        ctx: &egui::Context,
+        movie_db: &TheMovieDB,
+        library: &LibraryIds,
+        toasts: &mut Toasts,
+    ) -> Option<ViewAction> {
+        let collection_id = self.collection_id?;
+        let collection = match self.collection.poll() {
+            None => return None,
+            Some(Ok(collection)) => collection,
+            Some(Err(err)) => {
+                toasts.add(error_toast(format!("Failed to fetch {}: {err}", self.name)));
+                self.collection = Job::Empty;
+                self.collection_id = None;
+                return None;
+            }
+        };
+
+        let mut action = None;
+        let window = egui::Window::new(&self.name)
+            .id(Id::new(("collection", collection_id)))
+            .open(&mut self.window_open)
+            .default_width(400.0)
+            .resizable(true);
+
+        window.show(ctx, |ui| {
+            if !collection.overview.is_empty() {
+                ui.label(&collection.overview);
+                ui.separator();
+            }
+
+            let missing: Vec<Movie> = collection
+                .parts
+                .iter()
+                .filter(|movie| !library.contains_entry(false, movie.id))
+                .cloned()
+                .collect();
+            let owned = collection.parts.len() - missing.len();
+            let watched = collection.parts.iter().filter(|movie| library.is_movie_watched(movie.id)).count();
+            ui.horizontal(|ui| {
+                ui.label(format!(
+                    "{} films, {owned} in your library, {watched} watched",
+                    collection.parts.len()
+                ));
+                if ui.add_enabled(!missing.is_empty(), egui::Button::new("Add whole collection")).clicked() {
+                    action = Some(ViewAction::AddMovies(missing));
+                }
+            });
+            ui.separator();
+
+            egui::ScrollArea::vertical().id_source("collection_parts").show(ui, |ui| {
+                for movie in &collection.parts {
+                    ui.horizontal(|ui| {
+                        let image = match movie.poster_path.as_ref() {
+                            Some(path) => egui::Image::new(movie_db.get_image_url(path, ImageKind::Poster, 40.0)),
+                            None => egui::Image::new(include_image!("../res/no_image.png")),
+                        };
+                        ui.add_sized([40.0, 60.0], image);
+                        ui.vertical(|ui| {
+                            let in_library = library.contains_entry(false, movie.id);
+                            if in_library {
+                                ui.label(format!("\u{2714} {}", movie.title));
+                            } else {
+                                ui.label(&movie.title);
+                            }
+                            if movie.release_date.is_empty() {
+                                ui.weak("Unreleased");
+                            } else {
+                                ui.label(&movie.release_date);
+                            }
+                            if library.is_movie_watched(movie.id) {
+                                ui.weak("Watched");
+                            } else if in_library {
+                                ui.weak("Not watched yet");
+                            } else if ui.small_button("Add movie").clicked() {
+                                action = Some(ViewAction::AddProduction(Production::Movie(movie.clone())));
+                            }
+                        });
+                    });
+                }
+            });
+        });
+        action
+    }
+}
+
+impl ArtworkView {
+    pub fn new() -> Self {
+        Self {
+            window_open: false,
+            target: None,
+            title: String::new(),
+            images: Job::Empty,
+            kind: ArtworkKind::Posters,
+            language: None,
+        }
+    }
+
+    pub fn set_target(&mut self, is_tv: bool, id: u32, title: String, movie_db: &TheMovieDB) {
+        self.open(is_tv, id, title, movie_db.get_images(is_tv, id));
+    }
+
+    // The detail views already have the images, no need to fetch them again
+    pub fn set_images(&mut self, is_tv: bool, id: u32, title: String, images: Images) {
+        self.open(is_tv, id, title, Job::Finished(Ok(images)));
+    }
+
+    fn open(&mut self, is_tv: bool, id: u32, title: String, images: Job<TmdbResult<Images>>) {
+        self.target = Some((is_tv, id));
+        self.title = format!("Artwork of {title}");
+        self.images = images;
+        self.kind = ArtworkKind::Posters;
+        self.language = None;
+        self.window_open = true;
+    }
+
+    pub fn draw(
+        &mut self,
+        ctx: &egui::Context,
+        movie_db: &TheMovieDB,
+        library: &LibraryIds,
+        toasts: &mut Toasts,
+    ) -> Option<ViewAction> {
+        let (is_tv, id) = self.target?;
+        let images = match self.images.poll() {
+            None => return None,
+            Some(Ok(images)) => images,
+            Some(Err(err)) => {
+                toasts.add(error_toast(format!("Failed to fetch artwork: {err}")));
+                self.images = Job::Empty;
+                self.target = None;
+                return None;
+            }
+        };
+
+        let mut action = None;
+        let in_library = library.contains_entry(is_tv, id);
+        let window = egui::Window::new(&self.title)
+            .id(Id::new(("artwork", is_tv, id)))
+            .open(&mut self.window_open)
+            .default_width(560.0)
+            .default_height(500.0)
+            .resizable(true);
+
+        window.show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                for kind in ArtworkKind::ALL {
+                    ui.selectable_value(&mut self.kind, kind, kind.label());
+                }
+                ui.separator();
+
+                let artworks = artworks_of(images, self.kind);
+                let mut languages: Vec<Option<String>> = artworks.iter().map(|art| art.iso_639_1.clone()).collect();
+                languages.sort();
+                languages.dedup();
+
+                let language_label = |language: &Option<String>| match language {
+                    Some(code) => code.to_uppercase(),
+                    None => "No text".to_string(),
+                };
+                let selected = match &self.language {
+                    Some(language) => language_label(language),
+                    None => "All languages".to_string(),
+                };
+                egui::ComboBox::from_id_source("artwork_language").selected_text(selected).show_ui(ui, |ui| {
+                    ui.selectable_value(&mut self.language, None, "All languages");
+                    for language in languages {
+                        let label = language_label(&language);
+                        ui.selectable_value(&mut self.language, Some(language), label);
+                    }
+                });
+            });
+
+            if in_library {
+                ui.horizontal(|ui| {
+                    if ui.button("Use the default poster").clicked() {
+                        action = Some(ViewAction::SetPoster(is_tv, id, None));
+                    }
+                    if !is_tv && ui.button("Use the default backdrop").clicked() {
+                        action = Some(ViewAction::SetBackdrop(id, None));
+                    }
+                });
+            } else {
+                ui.label("Add the production to your library to pick its artwork");
+            }
+            ui.separator();
+
+            let artworks: Vec<&Artwork> = artworks_of(images, self.kind)
+                .iter()
+                .filter(|art| self.language.as_ref().is_none_or(|language| &art.iso_639_1 == language))
+                .collect();
+            if artworks.is_empty() {
+                ui.label("Nothing here");
+                return;
+            }
+
+            let size = self.kind.size();
+            egui::ScrollArea::vertical().auto_shrink([false, false]).show(ui, |ui| {
+                ui.horizontal_wrapped(|ui| {
+                    for art in artworks {
+                        ui.vertical(|ui| {
+                            ui.set_width(size.x);
+                            let (rect, response) = ui.allocate_exact_size(size, Sense::hover());
+                            // Hundreds of images per production, only the visible ones get loaded
+                            if ui.is_rect_visible(rect) {
+                                let image_url = movie_db.get_image_url(&art.file_path, self.kind.image_kind(), size.x);
+                                egui::Image::new(image_url).paint_at(ui, rect);
+                            }
+                            response.on_hover_text(format!(
+                                "{}x{}, rated {:.1} ({} votes)",
+                                art.width, art.height, art.vote_average, art.vote_count
+                            ));
+
+                            if !in_library {
+                                return;
+                            }
+                            match self.kind {
+                                ArtworkKind::Posters if ui.small_button("Use as poster").clicked() => {
+                                    action = Some(ViewAction::SetPoster(is_tv, id, Some(art.file_path.clone())));
+                                }
+                                ArtworkKind::Backdrops if !is_tv && ui.small_button("Use as header").clicked() => {
+                                    action = Some(ViewAction::SetBackdrop(id, Some(art.file_path.clone())));
+                                }
+                                _ => {}
+                            }
+                        });
+                    }
+                });
+            });
         });
+        action
+    }
+}
+
+fn artworks_of(images: &Images, kind: ArtworkKind) -> &[Artwork] {
+    match kind {
+        ArtworkKind::Posters => &images.posters,
+        ArtworkKind::Backdrops => &images.backdrops,
+        ArtworkKind::Logos => &images.logos,
     }
 }
 
@@ -257,6 +1004,127 @@ impl TrailersView {
     }
 }
 
+impl TasksView {
+    pub fn new() -> Self {
+        Self { is_open: false }
+    }
+
+    pub fn draw(&mut self, ctx: &egui::Context) {
+        if !self.is_open {
+            return;
+        }
+
+        let tasks = jobs::tasks();
+        let window = egui::Window::new("Tasks")
+            .open(&mut self.is_open)
+            .id("tasks".into())
+            .default_width(350.0)
+            .resizable(true);
+
+        window.show(ctx, |ui| {
+            if tasks.is_empty() {
+                ui.label("Nothing is running");
+                return;
+            }
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                egui::Grid::new("tasks_grid").num_columns(3).striped(true).show(ui, |ui| {
+                    for task in &tasks {
+                        ui.label(&task.label).on_hover_text(format!("{} priority", task.priority.label()));
+                        match &task.status {
+                            TaskStatus::Queued => {
+                                ui.weak("Queued");
+                            }
+                            TaskStatus::Running => match task.progress {
+                                Some((done, total)) if total > 0 => {
+                                    let progress = egui::ProgressBar::new(done as f32 / total as f32)
+                                        .text(format!("{done} / {total}"))
+                                        .desired_width(120.0);
+                                    ui.add(progress);
+                                }
+                                _ => {
+                                    ui.spinner();
+                                }
+                            },
+                            // Finished jobs are forgotten before they're marked done, this is never listed
+                            TaskStatus::Done => {
+                                ui.weak("Done");
+                            }
+                            TaskStatus::Failed(message) => {
+                                ui.colored_label(ui.visuals().error_fg_color, "Failed").on_hover_text(message);
+                            }
+                        }
+                        ui.horizontal(|ui| {
+                            if let TaskStatus::Failed(_) = task.status {
+                                let retry = ui.add_enabled(task.can_retry, egui::Button::new("Retry"));
+                                let retry = retry.on_disabled_hover_text("Nothing is waiting for the result anymore");
+                                if retry.clicked() {
+                                    jobs::retry_task(task.id);
+                                }
+                                if ui.button("Dismiss").clicked() {
+                                    jobs::cancel_task(task.id);
+                                }
+                            } else if ui.button("Cancel").clicked() {
+                                jobs::cancel_task(task.id);
+                            }
+                        });
+                        ui.end_row();
+                    }
+                });
+            });
+        });
+
+        // Workers don't wake the UI up, progress would only move with the mouse
+        if !tasks.is_empty() {
+            ctx.request_repaint_after(std::time::Duration::from_millis(250));
+        }
+    }
+}
+
+impl RefreshReportView {
+    pub fn new() -> Self {
+        Self {
+            is_open: false,
+            changes: Vec::new(),
+            failures: Vec::new(),
+        }
+    }
+
+    pub fn set_report(&mut self, changes: Vec<(String, Vec<String>)>, failures: Vec<String>) {
+        self.is_open = !changes.is_empty() || !failures.is_empty();
+        self.changes = changes;
+        self.failures = failures;
+    }
+
+    pub fn draw(&mut self, ctx: &egui::Context) {
+        if !self.is_open {
+            return;
+        }
+
+        let window = egui::Window::new("Refresh report")
+            .open(&mut self.is_open)
+            .id("refresh_report".into())
+            .resizable(true);
+
+        window.show(ctx, |ui| {
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                for (title, changes) in &self.changes {
+                    ui.strong(title);
+                    for change in changes {
+                        ui.label(format!("  {change}"));
+                    }
+                    ui.separator();
+                }
+                if !self.failures.is_empty() {
+                    ui.strong("Couldn't refresh");
+                    for failure in &self.failures {
+                        ui.label(format!("  {failure}"));
+                    }
+                }
+            });
+        });
+    }
+}
+
 impl LicenseView {
     pub fn new() -> Self {
         let id = "gpl".into();
@@ -281,3 +1149,235 @@ impl LicenseView {
         });
     }
 }
+
+// Collapsible strip of posters, titles that are already in the library are marked
+fn draw_related(
+    ui: &mut egui::Ui,
+    heading: &str,
+    related: &mut Job<TmdbResult<ProductionPage>>,
+    movie_db: &TheMovieDB,
+    library: &LibraryIds,
+) {
+    egui::CollapsingHeader::new(heading).show(ui, |ui| match related.poll() {
+        None => {
+            ui.spinner();
+        }
+        Some(Err(err)) => {
+            ui.label(format!("Failed to fetch: {err}"));
+        }
+        Some(Ok(page)) if page.productions.is_empty() => {
+            ui.label("Nothing found");
+        }
+        Some(Ok(page)) => {
+            egui::ScrollArea::horizontal().id_source(heading).show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    for prod in &page.productions {
+                        ui.vertical(|ui| {
+                            ui.set_width(80.0);
+                            let image = match prod.poster_path() {
+                                Some(path) => egui::Image::new(movie_db.get_image_url(path, ImageKind::Poster, 80.0)),
+                                None => egui::Image::new(include_image!("../res/no_image.png")),
+                            };
+                            ui.add_sized([80.0, 120.0], image);
+
+                            let title = if library.contains(prod) {
+                                format!("\u{2714} {}", prod.title())
+                            } else {
+                                prod.title().to_string()
+                            };
+                            let label = ui.add(Label::new(title).truncate(true));
+                            if library.contains(prod) {
+                                label.on_hover_text("Already in your library");
+                            } else {
+                                label.on_hover_text(prod.release_date());
+                            }
+                        });
+                    }
+                });
+            });
+        }
+    });
+}
+
+// IMDB page and trailers, both come with the details
+fn draw_links(ui: &mut egui::Ui, title: &str, external_ids: &ProductionIds, trailers: &[Trailer]) -> Option<ViewAction> {
+    ui.hyperlink_to("IMDB", external_ids.imdb_url(title));
+    let trailers_button = egui::Button::new(format!("Trailers ({})", trailers.len()));
+    if ui.add_enabled(!trailers.is_empty(), trailers_button).clicked() {
+        return Some(ViewAction::ShowTrailers(title.to_string(), trailers.to_vec()));
+    }
+    None
+}
+
+fn draw_keywords(ui: &mut egui::Ui, id_source: &str, keywords: &[Keyword]) {
+    egui::CollapsingHeader::new("Keywords").id_source(id_source).show(ui, |ui| {
+        if keywords.is_empty() {
+            ui.label("No keywords");
+            return;
+        }
+        ui.horizontal_wrapped(|ui| {
+            for keyword in keywords {
+                ui.label(egui::RichText::new(&keyword.name).weak());
+            }
+        });
+    });
+}
+
+// Long casts are cut short, every picture is a separate request
+const MAX_SHOWN_CAST: usize = 20;
+
+// Key crew followed by a strip of actors with their characters
+fn draw_credits(
+    ui: &mut egui::Ui,
+    id_source: &str,
+    credits: &Credits,
+    movie_db: &TheMovieDB,
+) -> Option<ViewAction> {
+    let mut action = None;
+    egui::CollapsingHeader::new("Cast & crew").id_source(id_source).show(ui, |ui| {
+        for (job, members) in credits.key_crew() {
+            ui.horizontal_wrapped(|ui| {
+                ui.label(format!("{job}:"));
+                for member in members {
+                    if ui.link(&member.name).clicked() {
+                        action = Some(ViewAction::OpenPerson(member.id, member.name.clone()));
+                    }
+                }
+            });
+        }
+
+        if credits.cast.is_empty() {
+            ui.label("No cast information");
+            return;
+        }
+
+        egui::ScrollArea::horizontal().id_source(id_source).show(ui, |ui| {
+            ui.horizontal(|ui| {
+                for actor in credits.cast.iter().take(MAX_SHOWN_CAST) {
+                    ui.vertical(|ui| {
+                        ui.set_width(80.0);
+                        let image = match actor.profile_path.as_ref() {
+                            Some(path) => egui::Image::new(movie_db.get_image_url(path, ImageKind::Profile, 80.0)),
+                            None => egui::Image::new(include_image!("../res/no_image.png")),
+                        };
+                        let picture = ui.add_sized([80.0, 120.0], image.sense(Sense::click()));
+                        let name = ui.add(Label::new(&actor.name).truncate(true).sense(Sense::click()));
+                        if picture.clicked() || name.clicked() {
+                            action = Some(ViewAction::OpenPerson(actor.id, actor.name.clone()));
+                        }
+
+                        let character = ui.add(Label::new(egui::RichText::new(&actor.character).weak()).truncate(true));
+                        if actor.episode_count > 0 {
+                            character.on_hover_text(format!("{} ({} episodes)", actor.character, actor.episode_count));
+                        } else {
+                            character.on_hover_text(&actor.character);
+                        }
+                    });
+                }
+                if credits.cast.len() > MAX_SHOWN_CAST {
+                    ui.label(format!("and {} more", credits.cast.len() - MAX_SHOWN_CAST));
+                }
+            });
+        });
+    });
+    action
+}
+
+// Provider logos grouped by the kind of offer, the name shows on hover
+pub fn draw_watch_providers(
+    ui: &mut egui::Ui,
+    id_source: &str,
+    providers: &mut Job<TmdbResult<WatchProviders>>,
+    movie_db: &TheMovieDB,
+) {
+    let heading = format!("Where to watch ({})", movie_db.region.to_uppercase());
+    egui::CollapsingHeader::new(heading).id_source(id_source).show(ui, |ui| {
+        let providers = match providers.poll() {
+            None => {
+                ui.spinner();
+                return;
+            }
+            Some(Err(err)) => {
+                ui.label(format!("Failed to fetch providers: {err}"));
+                return;
+            }
+            Some(Ok(providers)) => providers,
+        };
+
+        if providers.is_empty() {
+            ui.label("Not available in this region");
+            return;
+        }
+
+        egui::Grid::new(id_source).num_columns(2).show(ui, |ui| {
+            for (category, offers) in providers.categories() {
+                if offers.is_empty() {
+                    continue;
+                }
+                ui.label(category);
+                ui.horizontal_wrapped(|ui| {
+                    for provider in offers {
+                        let image = match provider.logo_path.as_ref() {
+                            Some(path) => egui::Image::new(movie_db.get_image_url(path, ImageKind::Logo, 32.0)),
+                            None => egui::Image::new(include_image!("../res/no_image.png")),
+                        };
+                        ui.add_sized([32.0, 32.0], image).on_hover_text(&provider.provider_name);
+                    }
+                });
+                ui.end_row();
+            }
+        });
+
+        if let Some(link) = &providers.link {
+            ui.hyperlink_to("All offers (JustWatch)", link);
+        }
+    });
+}
+
+// One row per country, the configured one comes first and stands out
+fn draw_releases(
+    ui: &mut egui::Ui,
+    id_source: &str,
+    heading: &str,
+    releases: &[CountryRelease],
+    movie_db: &TheMovieDB,
+) {
+    egui::CollapsingHeader::new(heading).id_source(id_source).show(ui, |ui| {
+        if releases.is_empty() {
+            ui.label("No release information");
+            return;
+        }
+
+        let mut countries: Vec<&CountryRelease> = releases.iter().collect();
+        countries.sort_by_key(|release| (!release.country.eq_ignore_ascii_case(&movie_db.region), &release.country));
+
+        egui::ScrollArea::vertical().id_source(id_source).max_height(200.0).show(ui, |ui| {
+            egui::Grid::new(id_source).num_columns(3).striped(true).show(ui, |ui| {
+                let highlight_color = ui.visuals().warn_fg_color;
+                for release in countries {
+                    let highlight = |text: &str| {
+                        let text = egui::RichText::new(text);
+                        if release.country.eq_ignore_ascii_case(&movie_db.region) {
+                            text.strong().color(highlight_color)
+                        } else {
+                            text
+                        }
+                    };
+                    ui.label(highlight(&release.country));
+                    let certification = if release.certification.is_empty() { "-" } else { &release.certification };
+                    ui.label(highlight(certification));
+                    ui.vertical(|ui| {
+                        for date in &release.releases {
+                            if date.note.is_empty() {
+                                ui.label(format!("{}: {}", date.kind.label(), date.date));
+                            } else {
+                                ui.label(format!("{}: {} ({})", date.kind.label(), date.date, date.note));
+                            }
+                        }
+                    });
+                    ui.end_row();
+                }
+            });
+        });
+    });
+}
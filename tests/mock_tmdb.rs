@@ -0,0 +1,171 @@
+// Starts the mock server over res/fixtures and checks the recorded responses have the shape the app reads
+use serde_json::Value;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpStream;
+use std::path::Path;
+use std::process::{Child, Command, Stdio};
+
+struct MockServer {
+    process: Child,
+    // host:port
+    address: String,
+    api_url: String,
+}
+
+impl MockServer {
+    fn start() -> Self {
+        let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("res/fixtures");
+        let mut process = Command::new(env!("CARGO_BIN_EXE_mock_tmdb"))
+            .arg("127.0.0.1:0")
+            .arg(fixtures)
+            .stdout(Stdio::piped())
+            .spawn()
+            .expect("Failed to start the mock server");
+
+        // "Replaying <dir> on http://127.0.0.1:<port>"
+        let mut stdout = BufReader::new(process.stdout.take().unwrap());
+        let mut line = String::new();
+        stdout.read_line(&mut line).unwrap();
+        let url = line.trim().rsplit(' ').next().unwrap().to_string();
+        let address = url.trim_start_matches("http://").to_string();
+        // Keep draining the request log so the server never blocks on a full pipe
+        std::thread::spawn(move || std::io::copy(&mut stdout, &mut std::io::sink()));
+
+        Self {
+            process,
+            api_url: format!("{url}/3"),
+            address,
+        }
+    }
+
+    fn get(&self, path: &str) -> Value {
+        ureq::get(&format!("{}{path}", self.api_url))
+            .call()
+            .unwrap_or_else(|err| panic!("GET {path} failed: {err}"))
+            .into_string()
+            .map(|body| serde_json::from_str(&body).unwrap())
+            .unwrap()
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        let _ = self.process.kill();
+        let _ = self.process.wait();
+    }
+}
+
+fn results(payload: &Value) -> &Vec<Value> {
+    payload["results"].as_array().expect("results should be an array")
+}
+
+#[test]
+fn search_results_are_pages_of_titles() {
+    let server = MockServer::start();
+    let multi = server.get("/search/multi?query=fight&page=1&language=en-US");
+    assert!(multi["total_pages"].as_u64().unwrap() >= 1);
+    assert!(!results(&multi).is_empty());
+    for result in results(&multi) {
+        assert!(result["id"].is_u64());
+        assert!(["movie", "tv", "person"].contains(&result["media_type"].as_str().unwrap()));
+    }
+
+    let movies = server.get("/search/movie?query=fight&year=1999");
+    assert!(results(&movies).iter().all(|movie| movie["title"].is_string() && movie["release_date"].is_string()));
+    let series = server.get("/search/tv?query=thrones&first_air_date_year=2011");
+    assert!(results(&series).iter().all(|series| series["name"].is_string() && series["first_air_date"].is_string()));
+}
+
+#[test]
+fn query_strings_are_ignored() {
+    let server = MockServer::start();
+    assert_eq!(server.get("/search/movie?query=a&page=1"), server.get("/search/movie?query=b&page=7&language=pl-PL"));
+}
+
+#[test]
+fn movie_details_carry_every_appended_response() {
+    let server = MockServer::start();
+    let movie = server.get("/movie/550?append_to_response=credits,videos,keywords,external_ids,images,release_dates");
+    assert_eq!(movie["id"], 550);
+    assert!(movie["title"].is_string());
+    for key in ["credits", "videos", "keywords", "external_ids", "images", "release_dates"] {
+        assert!(movie[key].is_object(), "movie/550 is missing {key}");
+    }
+    assert!(movie["keywords"]["keywords"].is_array());
+    assert!(movie["external_ids"]["imdb_id"].is_string());
+}
+
+#[test]
+fn series_details_and_seasons() {
+    let server = MockServer::start();
+    let series = server.get("/tv/1399?append_to_response=aggregate_credits,videos,keywords,external_ids");
+    assert_eq!(series["id"], 1399);
+    for key in ["aggregate_credits", "videos", "keywords", "external_ids"] {
+        assert!(series[key].is_object(), "tv/1399 is missing {key}");
+    }
+    let seasons = series["seasons"].as_array().unwrap();
+    assert_eq!(
+        seasons.iter().filter(|season| season["season_number"] != 0).count() as u64,
+        series["number_of_seasons"].as_u64().unwrap()
+    );
+
+    let season = server.get("/tv/1399/season/1");
+    let episodes = season["episodes"].as_array().unwrap();
+    let first_season = seasons.iter().find(|season| season["season_number"] == 1).unwrap();
+    // The recording keeps only the first few episodes
+    assert!(!episodes.is_empty());
+    assert!(episodes.len() as u64 <= first_season["episode_count"].as_u64().unwrap());
+    assert!(episodes.iter().all(|episode| episode["episode_number"].is_u64() && episode["name"].is_string()));
+
+    let episode = server.get("/tv/1399/season/1/episode/1");
+    assert_eq!(episode["episode_number"], 1);
+}
+
+#[test]
+fn videos_and_keywords() {
+    let server = MockServer::start();
+    for path in ["/movie/550/videos", "/tv/1399/videos"] {
+        let videos = server.get(path);
+        assert!(!results(&videos).is_empty(), "{path} has no videos");
+        assert!(results(&videos).iter().all(|video| video["key"].is_string() && video["site"].is_string()));
+    }
+
+    // Movies list keywords under "keywords", series under "results"
+    let movie_keywords = server.get("/movie/550/keywords");
+    assert!(!movie_keywords["keywords"].as_array().unwrap().is_empty());
+    let series_keywords = server.get("/tv/1399/keywords");
+    assert!(results(&series_keywords).iter().all(|keyword| keyword["name"].is_string()));
+}
+
+#[test]
+fn unknown_paths_get_tmdb_errors() {
+    let server = MockServer::start();
+    match ureq::get(&format!("{}/movie/1", server.api_url)).call() {
+        Err(ureq::Error::Status(404, response)) => {
+            let body: Value = serde_json::from_str(&response.into_string().unwrap()).unwrap();
+            assert_eq!(body["success"], false);
+        }
+        other => panic!("/movie/1 should be a 404, got {other:?}"),
+    }
+}
+
+// Sent as a raw request line, HTTP clients resolve the dots before anything reaches the server
+#[test]
+fn paths_cannot_leave_the_fixtures() {
+    let server = MockServer::start();
+    // res/fixtures/../fixtures/movie/550.json exists, only the path check keeps it from being served
+    for target in ["/3/../fixtures/movie/550", "/3/movie/../movie/550"] {
+        let mut stream = TcpStream::connect(&server.address).unwrap();
+        write!(stream, "GET {target} HTTP/1.1\r\nHost: {}\r\n\r\n", server.address).unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 404"), "{target} was answered with {response}");
+    }
+
+    // The same request without the dots is served
+    let mut stream = TcpStream::connect(&server.address).unwrap();
+    write!(stream, "GET /3/movie/550 HTTP/1.1\r\nHost: {}\r\n\r\n", server.address).unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    assert!(response.starts_with("HTTP/1.1 200"));
+}